@@ -0,0 +1,222 @@
+use syn::{
+    meta::ParseNestedMeta, Attribute, Expr, ExprLit, Ident, Lit, LitInt, LitStr, Meta,
+    MetaNameValue, Path, Token,
+};
+
+/// The options given to the derive with `#[variant_enum(...)]` on the enum.
+#[derive(Default)]
+pub(crate) struct EnumAttrs {
+    /// Replaces the generated doc comment of the variant enum.
+    pub doc: Option<LitStr>,
+    /// The name of the struct of slots to generate, if any.
+    pub set: Option<Ident>,
+    /// The traits to derive on the struct of slots.
+    pub set_derives: Vec<Path>,
+    /// Whether to generate a module of marker types, one per variant with a single field.
+    pub markers: bool,
+    /// Whether to generate the visitor traits, `{Name}Visitor` and `{Name}VisitorMut`.
+    pub visitor: bool,
+    /// Whether to derive `Serialize` and `Deserialize` on the variant enums.
+    pub serde: bool,
+    /// The path to the `serde` crate, if it is not `::serde`.
+    pub serde_crate: Option<LitStr>,
+}
+
+impl EnumAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("variant_enum"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("doc") {
+                    parsed.doc = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("set") && meta.input.peek(Token![=]) {
+                    let set: LitStr = meta.value()?.parse()?;
+                    parsed.set = Some(set.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("set") {
+                    let error = meta.error("missing `name = \"...\"` in `set(...)`");
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("name") {
+                            let set: LitStr = meta.value()?.parse()?;
+                            parsed.set = Some(set.parse()?);
+                            Ok(())
+                        } else if meta.path.is_ident("derive") {
+                            meta.parse_nested_meta(|meta| {
+                                parsed.set_derives.push(meta.path);
+                                Ok(())
+                            })
+                        } else {
+                            Err(meta.error("unknown `set` option"))
+                        }
+                    })?;
+                    if parsed.set.is_none() {
+                        return Err(error);
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("markers") {
+                    parsed.markers = true;
+                    Ok(())
+                } else if meta.path.is_ident("visitor") {
+                    parsed.visitor = true;
+                    Ok(())
+                } else if meta.path.is_ident("serde") {
+                    parsed.serde = true;
+                    if meta.input.is_empty() || meta.input.peek(Token![,]) {
+                        return Ok(());
+                    }
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("crate") {
+                            parsed.serde_crate = Some(meta.value()?.parse()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("unknown `serde` option"))
+                        }
+                    })
+                } else {
+                    Err(meta.error("unknown `variant_enum` option"))
+                }
+            })?;
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Returns the attributes of a source variant that are copied onto the matching variant of the variant enum: doc
+/// comments, deprecation, and lint levels.
+///
+/// `#[expect]` is not copied, since the expected lint is unlikely to be triggered by the variant enum.
+pub(crate) fn forwarded(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| {
+        let path = attr.path();
+        ["doc", "deprecated", "allow", "warn", "deny", "forbid"]
+            .iter()
+            .any(|name| path.is_ident(name))
+    })
+}
+
+/// Returns `true` if the attributes contain a doc comment.
+pub(crate) fn has_doc(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("doc"))
+}
+
+/// Returns the text of the doc comments in the attributes, one line per comment, with the space after `///` removed.
+pub(crate) fn doc_string(attrs: &[Attribute]) -> String {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }),
+                ..
+            }) => Some(doc.value()),
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>();
+
+    lines.join("\n").trim().to_string()
+}
+
+/// The options given with `#[variant(...)]` on a field of a variant.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// The field is part of the variant key.
+    pub key: bool,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("variant")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    parsed.key = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `variant` option for a field"))
+                }
+            })?;
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// The options given with `#[variant(...)]` on a variant.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// The name of the variant of the variant enum shared with other variants.
+    pub slot: Option<Ident>,
+    /// The stable ID of the variant.
+    pub id: Option<LitInt>,
+    /// The previous names of the variant, accepted when deserializing.
+    pub renamed_from: Vec<LitStr>,
+    /// The payload of the variant is boxed in the struct of slots.
+    pub boxed: bool,
+    /// The variants of the variant enum this variant can transition to.
+    pub transitions_to: Vec<Ident>,
+    /// The variants of the variant enum that must be present with this variant.
+    pub requires: Vec<Ident>,
+    /// The variants of the variant enum that must not be present with this variant.
+    pub conflicts_with: Vec<Ident>,
+    /// The variant does not implement `VariantOf` for the type of its field.
+    pub no_variant_of: bool,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("variant")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("slot") {
+                    let slot: LitStr = meta.value()?.parse()?;
+                    parsed.slot = Some(slot.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("id") {
+                    let id: LitInt = meta.value()?.parse()?;
+                    id.base10_parse::<u32>()?;
+                    parsed.id = Some(id);
+                    Ok(())
+                } else if meta.path.is_ident("renamed_from") {
+                    parsed.renamed_from.push(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("boxed") {
+                    parsed.boxed = true;
+                    Ok(())
+                } else if meta.path.is_ident("no_variant_of") {
+                    parsed.no_variant_of = true;
+                    Ok(())
+                } else if meta.path.is_ident("transitions_to") {
+                    parse_variant_list(&meta, &mut parsed.transitions_to)
+                } else if meta.path.is_ident("requires") {
+                    parse_variant_list(&meta, &mut parsed.requires)
+                } else if meta.path.is_ident("conflicts_with") {
+                    parse_variant_list(&meta, &mut parsed.conflicts_with)
+                } else {
+                    Err(meta.error("unknown `variant` option for a variant"))
+                }
+            })?;
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Parses a list of variant names, such as `(Running, Failed)` in `transitions_to(Running, Failed)`.
+fn parse_variant_list(meta: &ParseNestedMeta, variants: &mut Vec<Ident>) -> syn::Result<()> {
+    meta.parse_nested_meta(|variant| {
+        variants.push(variant.path.require_ident()?.clone());
+        Ok(())
+    })
+}
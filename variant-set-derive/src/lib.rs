@@ -1,7 +1,16 @@
 #![warn(clippy::all, clippy::pedantic)]
 
+use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitInt, Member, Type, Variant};
+
+use crate::{attrs::EnumAttrs, model::KeyVariant};
+
+mod attrs;
+mod markers;
+mod model;
+mod set;
+mod visitor;
 
 /// Derives a `_Variant` enum for the given enum, and derives the `VariantEnum` trait.
 ///
@@ -9,72 +18,640 @@ use syn::{parse_macro_input, Data, DeriveInput};
 /// each variant of the input enum, but without any data. This is used for the
 /// `VariantSet<T>` type, which is a set of variants of type T.
 ///
-/// # Panics
+/// The variant enum also implements `VariantKey`, `VariantKind` and `VariantReflect`, which describes its variants,
+/// their doc comments and their fields at runtime. Without key fields, it implements `FiniteKey` to list and name its
+/// variants.
+///
+/// The generated code only uses fully qualified paths, so the derive also works inside `#![no_implicit_prelude]`
+/// modules, and next to items that shadow standard names such as `From` or `Hash`:
+///
+/// ```
+/// mod no_prelude {
+///     #![no_implicit_prelude]
+///
+///     #[derive(::variant_set::VariantEnum)]
+///     #[variant_enum(set = "EventSet")]
+///     pub enum Event {
+///         Loaded(::std::vec::Vec<u8>),
+///         Closed,
+///     }
+/// }
+///
+/// mod shadowed {
+///     pub struct Hash(pub [u8; 32]);
+///     pub struct Debug;
+///     pub trait From {}
+///     pub trait Clone {}
+///     pub enum Option {}
+///     pub mod variant_set {}
+///     pub mod core {}
+///
+///     #[derive(::variant_set::VariantEnum)]
+///     pub enum Block {
+///         Mined { hash: Hash },
+///         Pending,
+///         // Named like the associated type of `VariantEnum`.
+///         Variant,
+///     }
+/// }
+///
+/// use variant_set::VariantSet;
+///
+/// let events = VariantSet::from([no_prelude::Event::Closed]);
+/// assert!(events.contains(no_prelude::EventVariant::Closed));
+/// assert!(no_prelude::EventSet::from(events).contains(no_prelude::EventVariant::Closed));
+///
+/// let blocks = VariantSet::from([shadowed::Block::Mined { hash: shadowed::Hash([0; 32]) }]);
+/// assert!(blocks.contains(shadowed::BlockVariant::Mined));
+/// ```
+///
+/// # Attributes
+///
+/// The generated variant enum is documented with a link back to the derived enum. Use
+/// `#[variant_enum(doc = "...")]` on the enum to replace that doc comment. Doc comments, `#[deprecated]` and lint
+/// attributes of each variant are copied onto the matching variant of the variant enum, so that the derive can be
+/// used on public enums in crates with `#![deny(missing_docs)]`:
+///
+/// ```
+/// #![deny(missing_docs)]
+/// //! A crate with documented enums.
+///
+/// use variant_set::VariantEnum;
+///
+/// /// A message sent to a server.
+/// #[derive(VariantEnum)]
+/// #[variant_enum(doc = "The kinds of messages sent to a server.")]
+/// pub enum Message {
+///     /// Checks that the server is alive.
+///     Ping,
+///     /// A text message.
+///     Text(String),
+///     /// Replaced by `Text`.
+///     #[deprecated]
+///     Legacy(Vec<u8>),
+/// }
+///
+/// # fn main() {
+/// let set = variant_set::VariantSet::from([Message::Ping]);
+/// assert!(set.contains(MessageVariant::Ping));
+/// # }
+/// ```
+///
+/// `#[variant_enum(...)]` on the enum accepts:
+///
+/// - `serde`, or `serde(crate = "...")` if the dependency is renamed, to derive `Serialize` and `Deserialize` on the
+///   generated enums with the `serde` crate of the deriving crate, for example to send a `VariantSetDiff`.
+/// - `set = "..."`, or `set(name = "...", derive(...))`, to generate a struct storing the data of each variant in its
+///   own field. See the generated sets of `VariantSet`.
+/// - `markers`, to generate a `VariantMarker` for each variant with a single field.
+/// - `visitor`, to generate the `{Name}Visitor` and `{Name}VisitorMut` traits. See `VariantVisit`.
+///
+/// `#[variant(...)]` on a variant accepts:
+///
+/// - `slot = "..."`, to share a variant of the variant enum with other variants. See `VariantEnum`.
+/// - `boxed`, to box the data of the variant in the struct generated with `set`.
+/// - `id = ...`, given to every variant or none, to implement `StableId`. IDs must be unique and at most 65535.
+/// - `renamed_from = "..."`, to keep deserializing the previous name of the variant with `serde`.
+/// - `transitions_to(...)`, to implement `DeclaredTransitions`.
+/// - `requires(...)` and `conflicts_with(...)`, to implement `VariantConstraints`.
+/// - `no_variant_of`, to not implement `VariantOf` for the type of its single field.
 ///
-/// Panics if the input is not an enum.
-#[proc_macro_derive(VariantEnum)]
+/// `#[variant(key)]` on a field makes it part of the variant, and generates a `{Name}Kind` enum. See `VariantKind`.
+/// Variants with key fields cannot be in a slot, have an ID, or declare transitions and constraints.
+#[proc_macro_derive(VariantEnum, attributes(variant_enum, variant))]
 pub fn derive_variant_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
     let name = &input.ident;
     let variants_enum_name = format_ident!("{}Variant", &input.ident);
 
-    let variants = match &input.data {
-        Data::Enum(data) => &data.variants,
-        _ => panic!("VariantEnum can only be derived for enums"),
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "VariantEnum can only be derived for enums",
+        ));
     };
 
-    let variant_idents: Vec<_> = variants.iter().map(|variant| &variant.ident).collect();
+    let enum_attrs = EnumAttrs::parse(&input.attrs)?;
+    let derives = expand_derives(&enum_attrs)?;
 
-    let enum_variants = variant_idents.iter().map(|variant| {
-        quote! {
-            #variant
+    let enum_doc = enum_attrs.doc.map_or_else(
+        || format!("The variants of [`{name}`], without their data."),
+        |doc| doc.value(),
+    );
+
+    let key_variants = model::key_variants(&data.variants)?;
+    let has_key_fields = key_variants.iter().any(|key| !key.fields.is_empty());
+
+    let enum_variants = key_variants.iter().map(|key| {
+        let docs = key.docs(name);
+        let variant_name = &key.ident;
+        let renamed_from = &key.renamed_from;
+        let alias = if enum_attrs.serde {
+            quote! { #(#[serde(alias = #renamed_from)])* }
+        } else {
+            quote! {}
+        };
+        if key.fields.is_empty() {
+            quote! { #docs #alias #variant_name }
+        } else {
+            let types = key.fields.iter().map(|(_, ty)| ty);
+            quote! { #docs #alias #variant_name(#(#types),*) }
         }
     });
 
-    let variant_cases = variants.iter().map(|variant| {
-        let variant_name = &variant.ident;
-        match &variant.fields {
-            syn::Fields::Unit => {
-                quote! {
-                    #name::#variant_name => #variants_enum_name::#variant_name,
+    let variant_enum = expand_variant_enum(name, &variants_enum_name, &key_variants);
+    let kind = expand_kind(name, &variants_enum_name, &derives, &key_variants);
+    let stable_id = expand_stable_id(&variants_enum_name, &key_variants);
+    let reflect = expand_reflect(&variants_enum_name, &key_variants);
+    let transitions = expand_transitions(&variants_enum_name, &key_variants);
+    let constraints = expand_constraints(&variants_enum_name, &key_variants);
+    let variant_of = expand_variant_of(name, &variants_enum_name, &key_variants);
+    let markers = enum_attrs
+        .markers
+        .then(|| markers::expand_markers(&input.vis, name, &variants_enum_name, &key_variants));
+    let visitor = enum_attrs
+        .visitor
+        .then(|| visitor::expand_visitor(&input.vis, name, &key_variants));
+    let set = set::expand_set(
+        &input.vis,
+        name,
+        &variants_enum_name,
+        enum_attrs.set.as_ref(),
+        &enum_attrs.set_derives,
+        &key_variants,
+    )?;
+    let ord = if has_key_fields {
+        quote! {}
+    } else {
+        quote! { #[derive(::core::cmp::PartialOrd, ::core::cmp::Ord)] }
+    };
+
+    // All paths are fully qualified so that the expansion does not depend on the prelude, or on what is in scope.
+    let expanded = quote! {
+        #[doc = #enum_doc]
+        #derives
+        #ord
+        pub enum #variants_enum_name {
+            #(#enum_variants),*
+        }
+
+        #kind
+
+        #stable_id
+
+        #reflect
+
+        #transitions
+
+        #constraints
+
+        #variant_of
+
+        #markers
+
+        #visitor
+
+        #set
+
+        #variant_enum
+    };
+
+    Ok(expanded)
+}
+
+/// Implements `VariantEnum` for the derived enum, and the conversion of its values into their variant.
+fn expand_variant_enum(
+    name: &Ident,
+    variants_enum_name: &Ident,
+    key_variants: &[KeyVariant],
+) -> TokenStream {
+    let variant_cases = key_variants.iter().flat_map(|key| {
+        let key_name = &key.ident;
+        let members = key.fields.iter().map(|(member, _)| member);
+        let bindings = (0..key.fields.len())
+            .map(|index| format_ident!("__key_{}", index))
+            .collect::<Vec<_>>();
+        let value = if key.fields.is_empty() {
+            quote! { #variants_enum_name::#key_name }
+        } else {
+            quote! { #variants_enum_name::#key_name(#(*#bindings),*) }
+        };
+
+        let patterns = key.sources.iter().map(move |source| {
+            let variant_name = &source.ident;
+            let members = members.clone();
+            quote! { #name::#variant_name { #(#members: #bindings,)* .. } }
+        });
+        quote! { #(#patterns)|* => #value, }
+    });
+
+    quote! {
+        #[allow(deprecated)]
+        impl ::core::convert::From<#name> for #variants_enum_name {
+            fn from(value: #name) -> Self {
+                <#name as ::variant_set::VariantEnum>::variant(&value)
+            }
+        }
+
+        #[allow(deprecated)]
+        impl ::variant_set::VariantEnum for #name {
+            type Variant = #variants_enum_name;
+
+            fn variant(&self) -> #variants_enum_name {
+                match self {
+                    #(#variant_cases)*
+                }
+            }
+        }
+    }
+}
+
+/// Returns the derives shared by the variant enum and the kind enum, including `Serialize` and `Deserialize` when
+/// asked for with `#[variant_enum(serde)]`.
+///
+/// `PartialOrd` and `Ord` are not part of these, since variant enums with key fields only require the fields to
+/// implement `Copy`, `Eq` and `Hash`.
+fn expand_derives(enum_attrs: &EnumAttrs) -> syn::Result<TokenStream> {
+    let derives = quote! {
+        #[derive(
+            ::core::fmt::Debug,
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash,
+        )]
+    };
+    if !enum_attrs.serde {
+        return Ok(derives);
+    }
+
+    let Some(serde_crate) = &enum_attrs.serde_crate else {
+        return Ok(quote! {
+            #derives
+            #[derive(::serde::Serialize, ::serde::Deserialize)]
+        });
+    };
+    let serde = serde_crate.parse::<syn::Path>()?;
+    Ok(quote! {
+        #derives
+        #[derive(#serde::Serialize, #serde::Deserialize)]
+        #[serde(crate = #serde_crate)]
+    })
+}
+
+/// Implements `VariantKey` and `VariantKind` for the variant enum.
+///
+/// Without key fields, the variant enum is its own kind. With key fields, a fieldless kind enum is generated, and the
+/// variant enum is hashed with `SipHash` since it no longer hashes as a single integer.
+fn expand_kind(
+    name: &Ident,
+    variants_enum_name: &Ident,
+    derives: &TokenStream,
+    key_variants: &[KeyVariant],
+) -> TokenStream {
+    let has_key_fields = key_variants.iter().any(|key| !key.fields.is_empty());
+
+    if !has_key_fields {
+        let finite_key = expand_finite_key(variants_enum_name, key_variants);
+        return quote! {
+            #finite_key
+
+            impl ::variant_set::VariantKey for #variants_enum_name {
+                type BuildHasher = ::variant_set::DenseIndex;
+            }
+
+            impl ::variant_set::VariantKind for #variants_enum_name {
+                type Kind = Self;
+
+                fn kind(self) -> Self {
+                    self
+                }
+            }
+        };
+    }
+
+    let kind_enum_name = format_ident!("{}Kind", name);
+    let finite_key = expand_finite_key(&kind_enum_name, key_variants);
+    let kind_doc = format!("The kinds of variants of [`{name}`], without their key fields.");
+    let kind_variants = key_variants.iter().map(|key| {
+        let docs = key.docs(name);
+        let variant_name = &key.ident;
+        quote! { #docs #variant_name }
+    });
+    let kind_cases = key_variants.iter().map(|key| {
+        let variant_name = &key.ident;
+        if key.fields.is_empty() {
+            quote! { #variants_enum_name::#variant_name => #kind_enum_name::#variant_name, }
+        } else {
+            quote! { #variants_enum_name::#variant_name(..) => #kind_enum_name::#variant_name, }
+        }
+    });
+
+    quote! {
+        #[doc = #kind_doc]
+        #derives
+        #[derive(::core::cmp::PartialOrd, ::core::cmp::Ord)]
+        pub enum #kind_enum_name {
+            #(#kind_variants),*
+        }
+
+        #finite_key
+
+        impl ::variant_set::VariantKey for #kind_enum_name {
+            type BuildHasher = ::variant_set::DenseIndex;
+        }
+
+        impl ::variant_set::VariantKey for #variants_enum_name {
+            type BuildHasher = ::variant_set::SipHash;
+        }
+
+        #[allow(deprecated)]
+        impl ::variant_set::VariantKind for #variants_enum_name {
+            type Kind = #kind_enum_name;
+
+            fn kind(self) -> #kind_enum_name {
+                match self {
+                    #(#kind_cases)*
                 }
             }
-            syn::Fields::Named(_) => {
-                quote! {
-                    #name::#variant_name { .. } => #variants_enum_name::#variant_name,
+        }
+    }
+}
+
+/// Implements `StableId` for the variant enum, if the variants were given IDs with `#[variant(id = ...)]`.
+///
+/// The IDs have already been checked to be present on every variant and unique by `model::key_variants`.
+fn expand_stable_id(variants_enum_name: &Ident, key_variants: &[KeyVariant]) -> TokenStream {
+    if key_variants.iter().any(|key| key.id.is_none()) {
+        return quote! {};
+    }
+
+    let variant_names = key_variants
+        .iter()
+        .map(|key| &key.ident)
+        .collect::<Vec<_>>();
+    let ids = key_variants
+        .iter()
+        .filter_map(|key| key.id.as_ref())
+        .map(|id| LitInt::new(&format!("{}u32", id.base10_digits()), id.span()))
+        .collect::<Vec<_>>();
+
+    quote! {
+        #[allow(deprecated)]
+        impl ::variant_set::StableId for #variants_enum_name {
+            fn id(self) -> ::core::primitive::u32 {
+                match self {
+                    #(#variants_enum_name::#variant_names => #ids,)*
                 }
             }
-            syn::Fields::Unnamed(_) => {
-                quote! {
-                    #name::#variant_name(..) => #variants_enum_name::#variant_name,
+
+            fn from_id(id: ::core::primitive::u32) -> ::core::option::Option<Self> {
+                match id {
+                    #(#ids => ::core::option::Option::Some(#variants_enum_name::#variant_names),)*
+                    _ => ::core::option::Option::None,
                 }
             }
         }
+    }
+}
+
+/// Implements `DeclaredTransitions` for the variant enum, if transitions were declared with
+/// `#[variant(transitions_to(...))]`.
+///
+/// The targets have already been checked to be variants of the variant enum by `model::key_variants`.
+fn expand_transitions(variants_enum_name: &Ident, key_variants: &[KeyVariant]) -> TokenStream {
+    if key_variants.iter().all(|key| key.transitions_to.is_empty()) {
+        return quote! {};
+    }
+
+    let edges = key_variants.iter().flat_map(|key| {
+        let from = &key.ident;
+        key.transitions_to.iter().map(move |to| {
+            quote! {
+                transitions.allow(#variants_enum_name::#from, #variants_enum_name::#to);
+            }
+        })
     });
 
-    let expanded = quote! {
-        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-        pub enum #variants_enum_name {
-            #(#enum_variants),*
+    quote! {
+        #[allow(deprecated)]
+        impl ::variant_set::DeclaredTransitions for #variants_enum_name {
+            fn transitions() -> ::variant_set::VariantTransitions<Self> {
+                let mut transitions = ::variant_set::VariantTransitions::new();
+                #(#edges)*
+                transitions
+            }
         }
+    }
+}
 
-        impl From<#name> for #variants_enum_name {
-            fn from(value: #name) -> Self {
-                <#name as variant_set::VariantEnum>::variant(&value)
+/// Implements `VariantConstraints` for the variant enum, if constraints were declared with `#[variant(requires(...))]`
+/// or `#[variant(conflicts_with(...))]`.
+///
+/// The named variants have already been checked to be variants of the variant enum by `model::key_variants`.
+fn expand_constraints(variants_enum_name: &Ident, key_variants: &[KeyVariant]) -> TokenStream {
+    if key_variants
+        .iter()
+        .all(|key| key.requires.is_empty() && key.conflicts_with.is_empty())
+    {
+        return quote! {};
+    }
+
+    let variant_names = key_variants
+        .iter()
+        .map(|key| &key.ident)
+        .collect::<Vec<_>>();
+    let requires = key_variants.iter().map(|key| &key.requires);
+    let conflicts_with = key_variants.iter().map(|key| &key.conflicts_with);
+
+    quote! {
+        #[allow(deprecated)]
+        impl ::variant_set::VariantConstraints for #variants_enum_name {
+            fn requires(self) -> &'static [Self] {
+                match self {
+                    #(#variants_enum_name::#variant_names => &[#(#variants_enum_name::#requires),*],)*
+                }
+            }
+
+            fn conflicts_with(self) -> &'static [Self] {
+                match self {
+                    #(#variants_enum_name::#variant_names => &[#(#variants_enum_name::#conflicts_with),*],)*
+                }
             }
         }
+    }
+}
 
-        impl variant_set::VariantEnum for #name {
-            type Variant = #variants_enum_name;
+/// Implements `VariantReflect` for the variant enum, describing each of its variants.
+fn expand_reflect(variants_enum_name: &Ident, key_variants: &[KeyVariant]) -> TokenStream {
+    let infos = key_variants.iter().enumerate().map(|(index, key)| {
+        let name = key.ident.to_string();
+        let (docs, fields) = if key.slot {
+            (String::new(), Vec::new())
+        } else {
+            let source = key.sources[0];
+            let fields = source
+                .fields
+                .iter()
+                .zip(source.fields.members())
+                .map(|(field, member)| {
+                    let name = match member {
+                        Member::Named(ident) => ident.to_string(),
+                        Member::Unnamed(index) => index.index.to_string(),
+                    };
+                    let type_name = model::type_name(&field.ty);
+                    quote! {
+                        ::variant_set::FieldInfo {
+                            name: #name,
+                            type_name: #type_name,
+                        }
+                    }
+                })
+                .collect();
+            (attrs::doc_string(&source.attrs), fields)
+        };
+
+        quote! {
+            ::variant_set::VariantInfo {
+                name: #name,
+                index: #index,
+                docs: #docs,
+                fields: &[#(#fields),*],
+            }
+        }
+    });
 
-            fn variant(&self) -> Self::Variant {
+    let index_cases = key_variants.iter().enumerate().map(|(index, key)| {
+        let variant_name = &key.ident;
+        if key.fields.is_empty() {
+            quote! { #variants_enum_name::#variant_name => #index, }
+        } else {
+            quote! { #variants_enum_name::#variant_name(..) => #index, }
+        }
+    });
+
+    quote! {
+        #[allow(deprecated)]
+        impl ::variant_set::VariantReflect for #variants_enum_name {
+            const VARIANTS: &'static [::variant_set::VariantInfo] = &[#(#infos),*];
+
+            fn variant_index(self) -> ::core::primitive::usize {
                 match self {
-                    #(#variant_cases)*
+                    #(#index_cases)*
                 }
             }
         }
-    };
+    }
+}
+
+/// Implements `VariantOf` for each tuple variant with a single field whose type, as written, is not the type of the
+/// single field of another tuple variant. Variants marked with `#[variant(no_variant_of)]` are left out, and do not
+/// count as using their type.
+fn expand_variant_of(
+    name: &Ident,
+    variants_enum_name: &Ident,
+    key_variants: &[KeyVariant],
+) -> TokenStream {
+    fn payload(variant: &Variant) -> Option<&Type> {
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(&fields.unnamed[0].ty),
+            _ => None,
+        }
+    }
 
-    proc_macro::TokenStream::from(expanded)
+    let type_names = key_variants
+        .iter()
+        .flat_map(|key| {
+            key.sources
+                .iter()
+                .filter(|source| !key.no_variant_of.contains(&source.ident))
+        })
+        .filter_map(|source| payload(source))
+        .map(model::type_name)
+        .collect::<Vec<_>>();
+
+    let impls = key_variants
+        .iter()
+        .filter(|key| key.fields.is_empty())
+        .flat_map(|key| key.sources.iter().map(move |source| (key, source)))
+        .filter(|(key, source)| !key.no_variant_of.contains(&source.ident))
+        .filter_map(|(key, source)| {
+            let ty = payload(source)?;
+            let type_name = model::type_name(ty);
+            if type_names.iter().filter(|name| **name == type_name).count() > 1 {
+                return None;
+            }
+
+            let key_name = &key.ident;
+            let variant_name = &source.ident;
+            Some(quote! {
+                #[allow(deprecated, unreachable_patterns)]
+                impl ::variant_set::VariantOf<#ty> for #name {
+                    const VARIANT: #variants_enum_name = #variants_enum_name::#key_name;
+
+                    fn from_payload(payload: #ty) -> Self {
+                        #name::#variant_name(payload)
+                    }
+
+                    fn payload(&self) -> ::core::option::Option<&#ty> {
+                        match self {
+                            #name::#variant_name(payload) => ::core::option::Option::Some(payload),
+                            _ => ::core::option::Option::None,
+                        }
+                    }
+
+                    fn payload_mut(&mut self) -> ::core::option::Option<&mut #ty> {
+                        match self {
+                            #name::#variant_name(payload) => ::core::option::Option::Some(payload),
+                            _ => ::core::option::Option::None,
+                        }
+                    }
+
+                    fn into_payload(self) -> ::core::option::Option<#ty> {
+                        match self {
+                            #name::#variant_name(payload) => ::core::option::Option::Some(payload),
+                            _ => ::core::option::Option::None,
+                        }
+                    }
+                }
+            })
+        });
+
+    quote! { #(#impls)* }
+}
+
+/// Implements `FiniteKey` for a fieldless enum with one variant per key variant, which is either the variant enum or
+/// the kind enum.
+fn expand_finite_key(enum_name: &Ident, key_variants: &[KeyVariant]) -> TokenStream {
+    let variant_names = key_variants
+        .iter()
+        .map(|key| &key.ident)
+        .collect::<Vec<_>>();
+    let names = variant_names.iter().map(ToString::to_string);
+
+    quote! {
+        #[allow(deprecated)]
+        impl ::variant_set::FiniteKey for #enum_name {
+            const COUNT: ::core::primitive::usize = Self::ALL.len();
+            const ALL: &'static [Self] = &[#(#enum_name::#variant_names),*];
+
+            fn index(self) -> ::core::primitive::usize {
+                self as ::core::primitive::usize
+            }
+
+            fn name(self) -> &'static ::core::primitive::str {
+                match self {
+                    #(#enum_name::#variant_names => #names,)*
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,142 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{Variant, Visibility};
+
+use crate::{model::KeyVariant, set::snake_case};
+
+/// Generates the module of marker types requested with `#[variant_enum(markers)]`, with a zero-sized type
+/// implementing `VariantMarker` for every variant with a single field and no key fields.
+///
+/// The markers are defined in `<enum>::markers`, but implement `VariantMarker` next to the enum, where the payload
+/// types are in scope.
+pub(crate) fn expand_markers(
+    vis: &Visibility,
+    name: &Ident,
+    variants_enum_name: &Ident,
+    key_variants: &[KeyVariant],
+) -> TokenStream {
+    let module = snake_case(name);
+    let marker_vis = nested_visibility(vis);
+    let markers = key_variants
+        .iter()
+        .filter(|key| key.fields.is_empty())
+        .flat_map(|key| key.sources.iter().map(move |source| (key, source)))
+        .filter(|(_, source)| source.fields.len() == 1)
+        .collect::<Vec<_>>();
+
+    let definitions = markers.iter().map(|(_, source)| {
+        let variant_name = &source.ident;
+        let doc = format!("The marker of the `{name}::{variant_name}` variant.");
+        quote! {
+            #[doc = #doc]
+            #[derive(
+                ::core::clone::Clone,
+                ::core::marker::Copy,
+                ::core::fmt::Debug,
+                ::core::default::Default,
+                ::core::cmp::PartialEq,
+                ::core::cmp::Eq,
+                ::core::hash::Hash,
+            )]
+            #marker_vis struct #variant_name;
+        }
+    });
+
+    let impls = markers
+        .iter()
+        .map(|(key, source)| expand_marker_impl(name, variants_enum_name, &module, key, source));
+
+    let module_doc = format!("Items generated for `{name}`.");
+    let markers_doc = format!(
+        "Zero-sized types standing for the variants of `{name}` with a single field, for use with \
+         `VariantSet::get_typed` and `VariantSet::set_typed`."
+    );
+
+    quote! {
+        #[doc = #module_doc]
+        #vis mod #module {
+            #[doc = #markers_doc]
+            pub mod markers {
+                #(#definitions)*
+            }
+        }
+
+        #(#impls)*
+    }
+}
+
+/// Implements `VariantMarker` for the marker of a source variant with a single field.
+fn expand_marker_impl(
+    name: &Ident,
+    variants_enum_name: &Ident,
+    module: &Ident,
+    key: &KeyVariant,
+    source: &Variant,
+) -> TokenStream {
+    let key_name = &key.ident;
+    let variant_name = &source.ident;
+    let field = source
+        .fields
+        .iter()
+        .next()
+        .expect("the variant has one field");
+    let member = source
+        .fields
+        .members()
+        .next()
+        .expect("the variant has one field");
+    let ty = &field.ty;
+    quote! {
+        #[allow(deprecated, unreachable_patterns)]
+        impl ::variant_set::VariantMarker for #module::markers::#variant_name {
+            type Enum = #name;
+            type Payload = #ty;
+
+            const VARIANT: #variants_enum_name = #variants_enum_name::#key_name;
+
+            fn from_payload(payload: #ty) -> #name {
+                #name::#variant_name { #member: payload }
+            }
+
+            fn payload(value: &#name) -> ::core::option::Option<&#ty> {
+                match value {
+                    #name::#variant_name { #member: payload } => ::core::option::Option::Some(payload),
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn payload_mut(value: &mut #name) -> ::core::option::Option<&mut #ty> {
+                match value {
+                    #name::#variant_name { #member: payload } => ::core::option::Option::Some(payload),
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            fn into_payload(value: #name) -> ::core::option::Option<#ty> {
+                match value {
+                    #name::#variant_name { #member: payload } => ::core::option::Option::Some(payload),
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    }
+}
+
+/// Returns the visibility of an item in `<enum>::markers` that is as visible as the enum, which is not private to
+/// the modules the markers are defined in. The markers cannot be more visible than the enum, since they name it.
+fn nested_visibility(vis: &Visibility) -> TokenStream {
+    match vis {
+        Visibility::Public(_) => quote! { #vis },
+        Visibility::Inherited => quote! { pub(in super::super) },
+        Visibility::Restricted(restricted) => {
+            let path = &restricted.path;
+            if path.segments[0].ident == "crate" || path.leading_colon.is_some() {
+                quote! { #vis }
+            } else if path.is_ident("self") {
+                quote! { pub(in super::super) }
+            } else {
+                quote! { pub(in super::super::#path) }
+            }
+        }
+    }
+}
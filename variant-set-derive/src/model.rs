@@ -0,0 +1,314 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{punctuated::Punctuated, Ident, LitInt, LitStr, Member, Token, Type, Variant};
+
+use crate::attrs::{self, FieldAttrs, VariantAttrs};
+
+/// A variant of the generated variant enum, and the variants of the derived enum that map to it.
+pub(crate) struct KeyVariant<'a> {
+    /// The name of the variant, which is the name of the source variant or of the slot.
+    pub ident: Ident,
+    /// The variants of the derived enum that map to this variant, more than one for a slot.
+    pub sources: Vec<&'a Variant>,
+    /// The fields marked with `#[variant(key)]`, carried by this variant.
+    pub fields: Vec<(Member, &'a Type)>,
+    /// Whether the variant was named with `#[variant(slot = "...")]`.
+    pub slot: bool,
+    /// The stable ID given with `#[variant(id = ...)]`.
+    pub id: Option<LitInt>,
+    /// The previous names given with `#[variant(renamed_from = "...")]`.
+    pub renamed_from: Vec<LitStr>,
+    /// Whether the payload is boxed in the struct of slots, with `#[variant(boxed)]`.
+    pub boxed: bool,
+    /// The variants given with `#[variant(transitions_to(...))]`, merged for a slot.
+    pub transitions_to: Vec<Ident>,
+    /// The variants given with `#[variant(requires(...))]`, merged for a slot.
+    pub requires: Vec<Ident>,
+    /// The variants given with `#[variant(conflicts_with(...))]`, merged for a slot.
+    pub conflicts_with: Vec<Ident>,
+    /// The source variants marked with `#[variant(no_variant_of)]`, which do not implement `VariantOf`.
+    pub no_variant_of: Vec<Ident>,
+}
+
+impl KeyVariant<'_> {
+    /// The doc comment and forwarded attributes of the variant.
+    ///
+    /// Attributes are only forwarded from the source variant when it is not part of a slot.
+    pub fn docs(&self, name: &Ident) -> TokenStream {
+        if self.slot {
+            let sources = self
+                .sources
+                .iter()
+                .map(|source| format!("[`{name}::{}`]", source.ident))
+                .collect::<Vec<_>>();
+            let doc = format!("The slot shared by {}.", sources.join(", "));
+            return quote! { #[doc = #doc] };
+        }
+
+        let source = self.sources[0];
+        let variant_name = &source.ident;
+        let forwarded = attrs::forwarded(&source.attrs);
+        let default_doc = if attrs::has_doc(&source.attrs) {
+            quote! {}
+        } else {
+            let doc = format!("The [`{name}::{variant_name}`] variant.");
+            quote! { #[doc = #doc] }
+        };
+
+        quote! {
+            #default_doc
+            #(#forwarded)*
+        }
+    }
+}
+
+/// Groups the variants of the derived enum into the variants of the variant enum, in declaration order.
+pub(crate) fn key_variants(
+    variants: &Punctuated<Variant, Token![,]>,
+) -> syn::Result<Vec<KeyVariant<'_>>> {
+    let mut key_variants: Vec<KeyVariant> = Vec::new();
+
+    for variant in variants {
+        let variant_attrs = VariantAttrs::parse(&variant.attrs)?;
+        let fields = key_fields(variant)?;
+
+        let VariantAttrs {
+            slot,
+            id,
+            mut renamed_from,
+            boxed,
+            mut transitions_to,
+            mut requires,
+            mut conflicts_with,
+            no_variant_of,
+        } = variant_attrs;
+        let mut no_variant_of = if no_variant_of {
+            vec![variant.ident.clone()]
+        } else {
+            Vec::new()
+        };
+
+        let Some(slot) = slot else {
+            if key_variants.iter().any(|key| key.ident == variant.ident) {
+                return Err(syn::Error::new_spanned(
+                    &variant.ident,
+                    "a slot with the same name as this variant already exists",
+                ));
+            }
+            key_variants.push(KeyVariant {
+                ident: variant.ident.clone(),
+                sources: vec![variant],
+                fields,
+                slot: false,
+                id,
+                renamed_from,
+                boxed,
+                transitions_to,
+                requires,
+                conflicts_with,
+                no_variant_of,
+            });
+            continue;
+        };
+
+        if !fields.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "variants in a slot cannot have key fields",
+            ));
+        }
+
+        match key_variants.iter_mut().find(|key| key.ident == slot) {
+            Some(key) if key.slot => {
+                match (&key.id, id) {
+                    (Some(existing), Some(id))
+                        if existing.base10_digits() != id.base10_digits() =>
+                    {
+                        return Err(syn::Error::new_spanned(
+                            id,
+                            "variants in a slot must have the same id",
+                        ));
+                    }
+                    (None, Some(id)) => key.id = Some(id),
+                    _ => {}
+                }
+                key.sources.push(variant);
+                key.renamed_from.append(&mut renamed_from);
+                key.boxed |= boxed;
+                key.transitions_to.append(&mut transitions_to);
+                key.requires.append(&mut requires);
+                key.conflicts_with.append(&mut conflicts_with);
+                key.no_variant_of.append(&mut no_variant_of);
+            }
+            Some(_) => {
+                return Err(syn::Error::new_spanned(
+                    slot,
+                    "a variant with the same name as this slot already exists",
+                ));
+            }
+            None => key_variants.push(KeyVariant {
+                ident: slot,
+                sources: vec![variant],
+                fields,
+                slot: true,
+                id,
+                renamed_from,
+                boxed,
+                transitions_to,
+                requires,
+                conflicts_with,
+                no_variant_of,
+            }),
+        }
+    }
+
+    check_ids(&key_variants)?;
+    check_variant_list(&key_variants, "transitions_to", |key| &key.transitions_to)?;
+    check_variant_list(&key_variants, "requires", |key| &key.requires)?;
+    check_variant_list(&key_variants, "conflicts_with", |key| &key.conflicts_with)?;
+    check_constraints(&key_variants)?;
+
+    Ok(key_variants)
+}
+
+/// The largest variant ID. `VariantSet::encode` writes a bitmap of the IDs present in the set, so IDs are kept small
+/// to bound its size.
+const MAX_ID: u32 = u16::MAX as u32;
+
+/// Checks that either no variant has a stable ID, or that all of them have distinct IDs up to `MAX_ID` and no key
+/// fields.
+fn check_ids(key_variants: &[KeyVariant]) -> syn::Result<()> {
+    if key_variants.iter().all(|key| key.id.is_none()) {
+        return Ok(());
+    }
+
+    let mut ids = Vec::new();
+    for key in key_variants {
+        let Some(id) = &key.id else {
+            return Err(syn::Error::new_spanned(
+                &key.sources[0].ident,
+                "missing `#[variant(id = ...)]`, every variant needs an id when one of them has one",
+            ));
+        };
+        if !key.fields.is_empty() {
+            return Err(syn::Error::new_spanned(
+                id,
+                "variants with key fields cannot have an id",
+            ));
+        }
+
+        let value = id.base10_parse::<u32>()?;
+        if value > MAX_ID {
+            return Err(syn::Error::new_spanned(
+                id,
+                format!("variant id {value} is too large, ids must be at most {MAX_ID}"),
+            ));
+        }
+        if ids.contains(&value) {
+            return Err(syn::Error::new_spanned(
+                id,
+                format!("duplicate variant id {value}"),
+            ));
+        }
+        ids.push(value);
+    }
+
+    Ok(())
+}
+
+/// Checks that the variants named in `#[variant(<option>(...))]`, returned by `list` for each variant, are variants of
+/// the variant enum, and that no variant has key fields when the option is used.
+fn check_variant_list<'k>(
+    key_variants: &'k [KeyVariant],
+    option: &str,
+    list: fn(&'k KeyVariant) -> &'k [Ident],
+) -> syn::Result<()> {
+    let Some(first) = key_variants.iter().find_map(|key| list(key).first()) else {
+        return Ok(());
+    };
+
+    if let Some(key) = key_variants.iter().find(|key| !key.fields.is_empty()) {
+        return Err(syn::Error::new_spanned(
+            first,
+            format!(
+                "`#[variant({option}(...))]` cannot be used with key fields, which `{}` has",
+                key.ident
+            ),
+        ));
+    }
+
+    for target in key_variants.iter().flat_map(list) {
+        if !key_variants.iter().any(|key| key.ident == *target) {
+            return Err(syn::Error::new_spanned(
+                target,
+                format!("no variant or slot named `{target}`"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that no variant conflicts with itself, or both requires and conflicts with the same variant, since no set
+/// containing such a variant could be valid.
+fn check_constraints(key_variants: &[KeyVariant]) -> syn::Result<()> {
+    for key in key_variants {
+        for target in &key.conflicts_with {
+            if *target == key.ident {
+                return Err(syn::Error::new_spanned(
+                    target,
+                    format!("`{target}` cannot conflict with itself"),
+                ));
+            }
+            if key.requires.contains(target) {
+                return Err(syn::Error::new_spanned(
+                    target,
+                    format!(
+                        "`{}` cannot both require and conflict with `{target}`",
+                        key.ident
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The fields marked with `#[variant(key)]` in a variant.
+fn key_fields(variant: &Variant) -> syn::Result<Vec<(Member, &Type)>> {
+    variant
+        .fields
+        .iter()
+        .zip(variant.fields.members())
+        .filter_map(|(field, member)| match FieldAttrs::parse(&field.attrs) {
+            Ok(attrs) if attrs.key => Some(Ok((member, &field.ty))),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        })
+        .collect()
+}
+
+/// Formats a type as it is usually written, since the `Display` output of tokens puts spaces between all of them.
+pub(crate) fn type_name(ty: &Type) -> String {
+    let mut name = ty.to_token_stream().to_string();
+    for (spaced, compact) in [
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        (" ::", "::"),
+        (":: ", "::"),
+        ("& ", "&"),
+        (" (", "("),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+        ("* ", "*"),
+    ] {
+        name = name.replace(spaced, compact);
+    }
+    name
+}
@@ -0,0 +1,858 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{ext::IdentExt, Path, Visibility};
+
+use crate::model::KeyVariant;
+
+/// Generates the struct of slots requested with `#[variant_enum(set = "...")]`, with one `Option` field per variant.
+///
+/// The payload of each variant is stored without its variant: as is for a single field, as a tuple for several fields,
+/// and as `()` for unit variants. Payloads of variants marked with `#[variant(boxed)]` are boxed.
+///
+/// The traits given with `#[variant_enum(set(name = "...", derive(...)))]` are derived on the struct, so that they are
+/// only implemented when asked for, and require the data of every variant to implement them.
+pub(crate) fn expand_set(
+    vis: &Visibility,
+    name: &Ident,
+    variants_enum_name: &Ident,
+    set_name: Option<&Ident>,
+    derives: &[Path],
+    key_variants: &[KeyVariant],
+) -> syn::Result<TokenStream> {
+    let Some(set_name) = set_name else {
+        if let Some(key) = key_variants.iter().find(|key| key.boxed) {
+            return Err(syn::Error::new_spanned(
+                &key.sources[0].ident,
+                "`#[variant(boxed)]` requires `#[variant_enum(set = \"...\")]` on the enum",
+            ));
+        }
+        return Ok(quote! {});
+    };
+
+    let slots = slots(name, set_name, key_variants)?;
+    let fields = slots.iter().map(|slot| &slot.field).collect::<Vec<_>>();
+    let variant_names = key_variants
+        .iter()
+        .map(|key| &key.ident)
+        .collect::<Vec<_>>();
+
+    let struct_doc = format!(
+        "A set of [`{name}`] values, storing the data of each variant in its own field.\n\nEquivalent to a \
+         `VariantSet<{name}>`, and convertible from and into one."
+    );
+    let field_defs = slots.iter().map(|slot| slot.definition(vis, name));
+    let set_cases = slots.iter().map(Slot::set_case);
+    let take_cases = slots.iter().map(|slot| slot.take_case(variants_enum_name));
+
+    let accessors = expand_accessors(vis, name, variants_enum_name, set_name, derives, &slots);
+    let borrowing_iterators = expand_borrowing_iterators(vis, variants_enum_name, set_name, &slots);
+    let owning_iterators = expand_owning_iterators(vis, name, variants_enum_name, set_name, &slots);
+    let exact = if derives.iter().any(|path| is_derive(path, "PartialEq")) {
+        expand_exact(vis, name, set_name, &slots)
+    } else {
+        quote! {}
+    };
+    let traits = expand_traits(name, set_name);
+
+    Ok(quote! {
+        #[doc = #struct_doc]
+        #[derive(#(#derives),*)]
+        #vis struct #set_name {
+            #(#field_defs,)*
+        }
+
+        #[allow(deprecated)]
+        impl #set_name {
+            /// Creates an empty set.
+            #[must_use]
+            #vis const fn new() -> Self {
+                Self {
+                    #(#fields: ::core::option::Option::None,)*
+                }
+            }
+
+            /// Returns the number of values in the set.
+            #[must_use]
+            #vis fn len(&self) -> ::core::primitive::usize {
+                let mut len = 0;
+                #(len += <::core::primitive::usize as ::core::convert::From<::core::primitive::bool>>::from(self.#fields.is_some());)*
+                len
+            }
+
+            /// Returns `true` if the set contains no values.
+            #[must_use]
+            #vis fn is_empty(&self) -> ::core::primitive::bool {
+                self.len() == 0
+            }
+
+            /// Removes all values from the set.
+            #vis fn clear(&mut self) {
+                *self = Self::new();
+            }
+
+            /// Returns `true` if the set contains a value of the given variant.
+            #[must_use]
+            #vis fn contains(&self, variant: #variants_enum_name) -> ::core::primitive::bool {
+                match variant {
+                    #(#variants_enum_name::#variant_names => self.#fields.is_some(),)*
+                }
+            }
+
+            /// Adds a value to the set if its variant is not present yet. Returns whether the value was inserted.
+            #vis fn insert(&mut self, value: #name) -> ::core::primitive::bool {
+                if self.contains(<#name as ::variant_set::VariantEnum>::variant(&value)) {
+                    false
+                } else {
+                    self.set(value);
+                    true
+                }
+            }
+
+            /// Adds a value to the set, replacing the value of the same variant. Returns the replaced value.
+            #vis fn set(&mut self, value: #name) -> ::core::option::Option<#name> {
+                match value {
+                    #(#set_cases)*
+                }
+            }
+
+            /// Removes the value of a variant from the set. Returns the value if it existed.
+            #vis fn remove(&mut self, variant: #variants_enum_name) -> ::core::option::Option<#name> {
+                match variant {
+                    #(#take_cases)*
+                }
+            }
+
+            /// Removes and returns the value of a variant, if any.
+            #vis fn take(&mut self, variant: #variants_enum_name) -> ::core::option::Option<#name> {
+                self.remove(variant)
+            }
+        }
+
+        #accessors
+
+        #borrowing_iterators
+
+        #owning_iterators
+
+        #exact
+
+        #traits
+    })
+}
+
+/// Builds the fields of the struct of slots, checking that every variant can have its own field and accessors.
+fn slots(name: &Ident, set_name: &Ident, key_variants: &[KeyVariant]) -> syn::Result<Vec<Slot>> {
+    if let Some(key) = key_variants
+        .iter()
+        .find(|key| key.slot || !key.fields.is_empty())
+    {
+        return Err(syn::Error::new_spanned(
+            &key.sources[0].ident,
+            "`#[variant_enum(set = \"...\")]` cannot be used with slots or key fields",
+        ));
+    }
+
+    let slots = key_variants
+        .iter()
+        .map(|key| Slot::new(name, key))
+        .collect::<Vec<_>>();
+    for (index, slot) in slots.iter().enumerate() {
+        // `get_or_insert` is a method of the struct.
+        if slot.field.unraw() == "or_insert" {
+            return Err(syn::Error::new_spanned(
+                &slot.variant,
+                format!(
+                    "`{}` would have an accessor named `get_or_insert`, which is already a method of `{set_name}`",
+                    slot.variant
+                ),
+            ));
+        }
+        for other in &slots[..index] {
+            if other.field == slot.field {
+                return Err(syn::Error::new_spanned(
+                    &slot.variant,
+                    format!(
+                        "`{}` and `{}` would both be stored in the field `{}` of `{set_name}`",
+                        other.variant, slot.variant, slot.field
+                    ),
+                ));
+            }
+            // `get_<variant>_mut` of one variant could be `get_<variant>` of another.
+            let (first, second) = (other.field.unraw(), slot.field.unraw());
+            if first == format!("{second}_mut") || second == format!("{first}_mut") {
+                return Err(syn::Error::new_spanned(
+                    &slot.variant,
+                    format!(
+                        "`{}` and `{}` would both have an accessor named `get_{}_mut` in `{set_name}`",
+                        other.variant,
+                        slot.variant,
+                        first.min(second)
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(slots)
+}
+
+/// Returns `true` if a path given to `derive(...)` names the given trait.
+fn is_derive(path: &Path, name: &str) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|segment| segment.ident == name)
+}
+
+/// Generates the accessors of the data of each variant, and the enums of references to the data of a variant that the
+/// struct of slots lends out, since it does not store values of the enum.
+fn expand_accessors(
+    vis: &Visibility,
+    name: &Ident,
+    variants_enum_name: &Ident,
+    set_name: &Ident,
+    derives: &[Path],
+    slots: &[Slot],
+) -> TokenStream {
+    let ref_name = format_ident!("{}Ref", set_name);
+    let mut_name = format_ident!("{}Mut", set_name);
+    let ref_doc = format!("A reference to the data of a variant in a [`{set_name}`].");
+    let mut_doc = format!("A mutable reference to the data of a variant in a [`{set_name}`].");
+    // The references implement the traits derived on the struct that only look at the data.
+    let ref_derives = derives
+        .iter()
+        .filter(|path| {
+            ["Debug", "PartialEq", "Eq", "Hash"]
+                .iter()
+                .any(|name| is_derive(path, name))
+        })
+        .collect::<Vec<_>>();
+
+    let variants = slots.iter().map(|slot| &slot.variant).collect::<Vec<_>>();
+    let data = slots.iter().map(|slot| &slot.data).collect::<Vec<_>>();
+    let data_docs = slots
+        .iter()
+        .map(|slot| format!("The data of the [`{name}::{}`] variant.", slot.variant))
+        .collect::<Vec<_>>();
+    let getters = slots.iter().map(|slot| slot.getters(vis, name));
+    let get_cases = slots.iter().map(|slot| {
+        let variant = &slot.variant;
+        let getter = format_ident!("get_{}", slot.field);
+        quote! {
+            #variants_enum_name::#variant => ::core::option::Option::map(self.#getter(), #ref_name::#variant),
+        }
+    });
+    let insert_cases = slots.iter().map(|slot| {
+        let Slot {
+            variant,
+            field,
+            pattern,
+            pack,
+            ..
+        } = slot;
+        quote! {
+            #pattern => #ref_name::#variant(self.#field.get_or_insert_with(|| #pack)),
+        }
+    });
+
+    quote! {
+        #[doc = #ref_doc]
+        #[derive(::core::clone::Clone, ::core::marker::Copy, #(#ref_derives),*)]
+        #vis enum #ref_name<'a> {
+            #(
+                #[doc = #data_docs]
+                #variants(&'a #data),
+            )*
+        }
+
+        impl #ref_name<'_> {
+            /// Returns the variant of the referenced data.
+            #[must_use]
+            #vis fn variant(self) -> #variants_enum_name {
+                match self {
+                    #(Self::#variants(_) => #variants_enum_name::#variants,)*
+                }
+            }
+        }
+
+        #[doc = #mut_doc]
+        #[derive(#(#ref_derives),*)]
+        #vis enum #mut_name<'a> {
+            #(
+                #[doc = #data_docs]
+                #variants(&'a mut #data),
+            )*
+        }
+
+        impl #mut_name<'_> {
+            /// Returns the variant of the referenced data.
+            #[must_use]
+            #vis fn variant(&self) -> #variants_enum_name {
+                match self {
+                    #(Self::#variants(_) => #variants_enum_name::#variants,)*
+                }
+            }
+        }
+
+        #[allow(deprecated)]
+        impl #set_name {
+            #(#getters)*
+
+            /// Returns a reference to the data of a variant, if present.
+            #[must_use]
+            #vis fn get(&self, variant: #variants_enum_name) -> ::core::option::Option<#ref_name<'_>> {
+                match variant {
+                    #(#get_cases)*
+                }
+            }
+
+            /// Adds a value to the set if its variant is not present yet, then returns a reference to the data of its
+            /// variant in the set.
+            #vis fn get_or_insert(&mut self, default: #name) -> #ref_name<'_> {
+                match default {
+                    #(#insert_cases)*
+                }
+            }
+
+            /// Returns an iterator over the variants present in the set and references to their data, in declaration
+            /// order.
+            #vis fn iter_pairs(&self) -> impl ::core::iter::Iterator<Item = (#variants_enum_name, #ref_name<'_>)> + '_ {
+                ::core::iter::Iterator::map(self.iter(), |data| (data.variant(), data))
+            }
+        }
+    }
+}
+
+/// Generates the borrowing and mutably borrowing iterators of the struct of slots, which visit the variants in
+/// declaration order.
+fn expand_borrowing_iterators(
+    vis: &Visibility,
+    variants_enum_name: &Ident,
+    set_name: &Ident,
+    slots: &[Slot],
+) -> TokenStream {
+    let ref_name = format_ident!("{}Ref", set_name);
+    let mut_name = format_ident!("{}Mut", set_name);
+    let iter_name = format_ident!("{}Iter", set_name);
+    let iter_mut_name = format_ident!("{}IterMut", set_name);
+    let iter_doc =
+        format!("An iterator over references to the data of the variants in a [`{set_name}`].");
+    let iter_mut_doc = format!(
+        "An iterator over mutable references to the data of the variants in a [`{set_name}`]."
+    );
+
+    let fields = slots.iter().map(|slot| &slot.field).collect::<Vec<_>>();
+    let variants = slots.iter().map(|slot| &slot.variant).collect::<Vec<_>>();
+    let data = slots.iter().map(|slot| &slot.data).collect::<Vec<_>>();
+    let borrows = slots.iter().map(|slot| slot.borrow(false));
+    let borrows_mut = slots.iter().map(|slot| slot.borrow(true));
+
+    let iter_impls = expand_borrowing_iterator(&iter_name, &ref_name, slots);
+    let iter_mut_impls = expand_borrowing_iterator(&iter_mut_name, &mut_name, slots);
+
+    quote! {
+        #[doc = #iter_doc]
+        #[derive(::core::clone::Clone)]
+        #vis struct #iter_name<'a> {
+            #(#fields: ::core::option::Option<&'a #data>,)*
+        }
+
+        #iter_impls
+
+        #[doc = #iter_mut_doc]
+        #vis struct #iter_mut_name<'a> {
+            #(#fields: ::core::option::Option<&'a mut #data>,)*
+        }
+
+        #iter_mut_impls
+
+        #[allow(deprecated)]
+        impl #set_name {
+            /// Returns an iterator over the variants present in the set, in declaration order.
+            #vis fn variants(&self) -> impl ::core::iter::Iterator<Item = #variants_enum_name> + '_ {
+                let variants = [#((self.#fields.is_some(), #variants_enum_name::#variants)),*];
+                ::core::iter::Iterator::filter_map(
+                    ::core::iter::IntoIterator::into_iter(variants),
+                    |(present, variant)| present.then_some(variant),
+                )
+            }
+
+            /// Returns an iterator over the variants missing from the set, in declaration order.
+            #vis fn missing(&self) -> impl ::core::iter::Iterator<Item = #variants_enum_name> + '_ {
+                let variants = [#((self.#fields.is_none(), #variants_enum_name::#variants)),*];
+                ::core::iter::Iterator::filter_map(
+                    ::core::iter::IntoIterator::into_iter(variants),
+                    |(missing, variant)| missing.then_some(variant),
+                )
+            }
+
+            /// Returns an iterator over references to the data of the variants present in the set, in declaration
+            /// order.
+            #vis fn iter(&self) -> #iter_name<'_> {
+                #iter_name {
+                    #(#fields: #borrows,)*
+                }
+            }
+
+            /// Returns an iterator over mutable references to the data of the variants present in the set, in
+            /// declaration order.
+            #vis fn iter_mut(&mut self) -> #iter_mut_name<'_> {
+                #iter_mut_name {
+                    #(#fields: #borrows_mut,)*
+                }
+            }
+        }
+
+        impl<'a> ::core::iter::IntoIterator for &'a #set_name {
+            type Item = #ref_name<'a>;
+            type IntoIter = #iter_name<'a>;
+
+            fn into_iter(self) -> #iter_name<'a> {
+                self.iter()
+            }
+        }
+
+        impl<'a> ::core::iter::IntoIterator for &'a mut #set_name {
+            type Item = #mut_name<'a>;
+            type IntoIter = #iter_mut_name<'a>;
+
+            fn into_iter(self) -> #iter_mut_name<'a> {
+                self.iter_mut()
+            }
+        }
+    }
+}
+
+/// Implements the iterator traits for a borrowing iterator of the struct of slots, which holds a reference to the data
+/// of each variant and yields them as `item`.
+fn expand_borrowing_iterator(iter_name: &Ident, item: &Ident, slots: &[Slot]) -> TokenStream {
+    let fields = slots.iter().map(|slot| &slot.field).collect::<Vec<_>>();
+    let variants = slots.iter().map(|slot| &slot.variant).collect::<Vec<_>>();
+    let rev_fields = fields.iter().rev();
+    let rev_variants = variants.iter().rev();
+
+    quote! {
+        impl<'a> ::core::iter::Iterator for #iter_name<'a> {
+            type Item = #item<'a>;
+
+            fn next(&mut self) -> ::core::option::Option<Self::Item> {
+                #(
+                    if let ::core::option::Option::Some(data) = self.#fields.take() {
+                        return ::core::option::Option::Some(#item::#variants(data));
+                    }
+                )*
+                ::core::option::Option::None
+            }
+
+            fn size_hint(&self) -> (::core::primitive::usize, ::core::option::Option<::core::primitive::usize>) {
+                let mut len = 0;
+                #(len += <::core::primitive::usize as ::core::convert::From<::core::primitive::bool>>::from(self.#fields.is_some());)*
+                (len, ::core::option::Option::Some(len))
+            }
+        }
+
+        impl ::core::iter::DoubleEndedIterator for #iter_name<'_> {
+            fn next_back(&mut self) -> ::core::option::Option<Self::Item> {
+                #(
+                    if let ::core::option::Option::Some(data) = self.#rev_fields.take() {
+                        return ::core::option::Option::Some(#item::#rev_variants(data));
+                    }
+                )*
+                ::core::option::Option::None
+            }
+        }
+
+        impl ::core::iter::ExactSizeIterator for #iter_name<'_> {}
+
+        impl ::core::iter::FusedIterator for #iter_name<'_> {}
+    }
+}
+
+/// Generates the owning and extracting iterators of the struct of slots, which visit the variants in declaration
+/// order.
+fn expand_owning_iterators(
+    vis: &Visibility,
+    name: &Ident,
+    variants_enum_name: &Ident,
+    set_name: &Ident,
+    slots: &[Slot],
+) -> TokenStream {
+    let into_iter_name = format_ident!("{}IntoIter", set_name);
+    let extract_if_name = format_ident!("{}ExtractIf", set_name);
+    let into_iter_doc = format!("An iterator moving the values out of a [`{set_name}`].");
+    let extract_if_doc = format!(
+        "An iterator removing the values of a [`{set_name}`] that match a predicate, as returned by \
+         [`{set_name}::extract_if`]."
+    );
+    let variants = slots.iter().map(|slot| &slot.variant).collect::<Vec<_>>();
+    let rev_variants = variants.iter().rev();
+
+    quote! {
+        #[doc = #into_iter_doc]
+        #vis struct #into_iter_name {
+            set: #set_name,
+        }
+
+        #[allow(deprecated)]
+        impl ::core::iter::Iterator for #into_iter_name {
+            type Item = #name;
+
+            fn next(&mut self) -> ::core::option::Option<#name> {
+                #(
+                    if let ::core::option::Option::Some(value) = self.set.remove(#variants_enum_name::#variants) {
+                        return ::core::option::Option::Some(value);
+                    }
+                )*
+                ::core::option::Option::None
+            }
+
+            fn size_hint(&self) -> (::core::primitive::usize, ::core::option::Option<::core::primitive::usize>) {
+                (self.set.len(), ::core::option::Option::Some(self.set.len()))
+            }
+        }
+
+        #[allow(deprecated)]
+        impl ::core::iter::DoubleEndedIterator for #into_iter_name {
+            fn next_back(&mut self) -> ::core::option::Option<#name> {
+                #(
+                    if let ::core::option::Option::Some(value) = self.set.remove(#variants_enum_name::#rev_variants) {
+                        return ::core::option::Option::Some(value);
+                    }
+                )*
+                ::core::option::Option::None
+            }
+        }
+
+        impl ::core::iter::ExactSizeIterator for #into_iter_name {}
+
+        impl ::core::iter::FusedIterator for #into_iter_name {}
+
+        #[doc = #extract_if_doc]
+        #vis struct #extract_if_name<'a, F> {
+            set: &'a mut #set_name,
+            index: ::core::primitive::usize,
+            pred: F,
+        }
+
+        #[allow(deprecated)]
+        impl<F> ::core::iter::Iterator for #extract_if_name<'_, F>
+        where
+            F: ::core::ops::FnMut(&mut #name) -> ::core::primitive::bool,
+        {
+            type Item = #name;
+
+            fn next(&mut self) -> ::core::option::Option<#name> {
+                const VARIANTS: &[#variants_enum_name] = &[#(#variants_enum_name::#variants),*];
+
+                while let ::core::option::Option::Some(&variant) = VARIANTS.get(self.index) {
+                    self.index += 1;
+                    if let ::core::option::Option::Some(mut value) = self.set.remove(variant) {
+                        if (self.pred)(&mut value) {
+                            return ::core::option::Option::Some(value);
+                        }
+                        self.set.set(value);
+                    }
+                }
+                ::core::option::Option::None
+            }
+        }
+
+        #[allow(deprecated)]
+        impl #set_name {
+            /// Removes all values from the set, returning them as an iterator in declaration order.
+            #vis fn drain(&mut self) -> #into_iter_name {
+                #into_iter_name {
+                    set: ::core::mem::replace(self, Self::new()),
+                }
+            }
+
+            /// Returns an iterator which removes and yields every value for which `pred` returns `true`, in
+            /// declaration order. Values for which `pred` returns `false` are kept in the set.
+            #vis fn extract_if<F>(&mut self, pred: F) -> #extract_if_name<'_, F>
+            where
+                F: ::core::ops::FnMut(&mut #name) -> ::core::primitive::bool,
+            {
+                #extract_if_name {
+                    set: self,
+                    index: 0,
+                    pred,
+                }
+            }
+        }
+
+        impl ::core::iter::IntoIterator for #set_name {
+            type Item = #name;
+            type IntoIter = #into_iter_name;
+
+            fn into_iter(self) -> #into_iter_name {
+                #into_iter_name { set: self }
+            }
+        }
+    }
+}
+
+/// Generates `contains_exact` and `remove_exact`, which compare the data of a variant, when `PartialEq` is derived on
+/// the struct of slots.
+fn expand_exact(vis: &Visibility, name: &Ident, set_name: &Ident, slots: &[Slot]) -> TokenStream {
+    let cases = slots.iter().map(|slot| {
+        let getter = format_ident!("get_{}", slot.field);
+        let pattern = &slot.pattern;
+        let comparisons = slot.comparisons();
+        quote! {
+            #pattern => match self.#getter() {
+                ::core::option::Option::Some(__data) => true #(&& #comparisons)*,
+                ::core::option::Option::None => false,
+            },
+        }
+    });
+    quote! {
+        #[allow(deprecated)]
+        impl #set_name {
+            /// Returns `true` if the set contains the given value, comparing the data of its variant.
+            #[must_use]
+            #vis fn contains_exact(&self, value: &#name) -> ::core::primitive::bool {
+                match value {
+                    #(#cases)*
+                }
+            }
+
+            /// Removes the given value from the set if it is present, comparing the data of its variant. Returns the
+            /// removed value.
+            #vis fn remove_exact(&mut self, value: &#name) -> ::core::option::Option<#name> {
+                if self.contains_exact(value) {
+                    self.remove(<#name as ::variant_set::VariantEnum>::variant(value))
+                } else {
+                    ::core::option::Option::None
+                }
+            }
+        }
+    }
+}
+
+/// Implements the conversion and collection traits of the struct of slots, which are the same as for `VariantSet`.
+fn expand_traits(name: &Ident, set_name: &Ident) -> TokenStream {
+    quote! {
+        impl ::core::default::Default for #set_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl ::core::iter::Extend<#name> for #set_name {
+            fn extend<I: ::core::iter::IntoIterator<Item = #name>>(&mut self, iter: I) {
+                for value in iter {
+                    self.set(value);
+                }
+            }
+        }
+
+        impl ::core::iter::FromIterator<#name> for #set_name {
+            fn from_iter<I: ::core::iter::IntoIterator<Item = #name>>(iter: I) -> Self {
+                let mut set = Self::new();
+                ::core::iter::Extend::extend(&mut set, iter);
+                set
+            }
+        }
+
+        impl ::core::convert::From<::variant_set::VariantSet<#name>> for #set_name {
+            fn from(set: ::variant_set::VariantSet<#name>) -> Self {
+                ::core::iter::Iterator::collect(::core::iter::IntoIterator::into_iter(set))
+            }
+        }
+
+        impl ::core::convert::From<#set_name> for ::variant_set::VariantSet<#name> {
+            fn from(set: #set_name) -> Self {
+                ::core::iter::Iterator::collect(::core::iter::IntoIterator::into_iter(set))
+            }
+        }
+    }
+}
+
+/// The field of the struct of slots storing a variant, and how values are moved in and out of it.
+struct Slot {
+    variant: Ident,
+    field: Ident,
+    /// The type stored in the field, which is the data of the variant, boxed if the variant is marked as boxed.
+    payload: TokenStream,
+    /// The data of the variant: its single field, a tuple of its fields, or `()`.
+    data: TokenStream,
+    /// The number of fields of the variant.
+    len: usize,
+    boxed: bool,
+    /// Matches a value of the variant, binding its fields.
+    pattern: TokenStream,
+    /// Builds the payload from the bound fields.
+    pack: TokenStream,
+    /// A closure turning the payload back into a value.
+    unpack: TokenStream,
+}
+
+impl Slot {
+    fn new(name: &Ident, key: &KeyVariant) -> Self {
+        let source = key.sources[0];
+        let variant = source.ident.clone();
+        let members = source.fields.members().collect::<Vec<_>>();
+        let types = source
+            .fields
+            .iter()
+            .map(|field| &field.ty)
+            .collect::<Vec<_>>();
+        let bindings = (0..members.len())
+            .map(|index| format_ident!("__field_{}", index))
+            .collect::<Vec<_>>();
+
+        // The payload is built and destructured with the same tokens, such as `__field_0` or `(__field_0, __field_1,)`.
+        let (data, bound) = match types.as_slice() {
+            [ty] => (quote! { #ty }, quote! { #(#bindings)* }),
+            _ => (quote! { (#(#types,)*) }, quote! { (#(#bindings,)*) }),
+        };
+        let value = quote! { #name::#variant { #(#members: #bindings),* } };
+
+        let (payload, pack, unpack) = if key.boxed {
+            (
+                quote! { ::std::boxed::Box<#data> },
+                quote! { ::std::boxed::Box::new(#bound) },
+                quote! {
+                    |__payload: ::std::boxed::Box<#data>| {
+                        let #bound = *__payload;
+                        #value
+                    }
+                },
+            )
+        } else {
+            (data.clone(), bound.clone(), quote! { |#bound| #value })
+        };
+
+        Self {
+            field: snake_case(&variant),
+            variant,
+            payload,
+            data,
+            len: members.len(),
+            boxed: key.boxed,
+            pattern: value,
+            pack,
+            unpack,
+        }
+    }
+
+    /// The definition of the field in the struct.
+    fn definition(&self, vis: &Visibility, name: &Ident) -> TokenStream {
+        let field = &self.field;
+        let payload = &self.payload;
+        let doc = format!(
+            "The data of the [`{name}::{}`] variant, if present.",
+            self.variant
+        );
+        quote! {
+            #[doc = #doc]
+            #vis #field: ::core::option::Option<#payload>
+        }
+    }
+
+    /// Borrows the data of the variant from its field, mutably or not.
+    fn borrow(&self, mutable: bool) -> TokenStream {
+        let field = &self.field;
+        match (self.boxed, mutable) {
+            (false, false) => quote! { self.#field.as_ref() },
+            (false, true) => quote! { self.#field.as_mut() },
+            (true, false) => quote! { self.#field.as_deref() },
+            (true, true) => quote! { self.#field.as_deref_mut() },
+        }
+    }
+
+    /// The `get_<variant>` and `get_<variant>_mut` methods, returning references to the data of the variant.
+    fn getters(&self, vis: &Visibility, name: &Ident) -> TokenStream {
+        let data = &self.data;
+        let getter = format_ident!("get_{}", self.field);
+        let getter_mut = format_ident!("get_{}_mut", self.field);
+        let (borrow, borrow_mut) = (self.borrow(false), self.borrow(true));
+        let doc = format!(
+            "Returns a reference to the data of the [`{name}::{}`] variant, if present.",
+            self.variant
+        );
+        let doc_mut = format!(
+            "Returns a mutable reference to the data of the [`{name}::{}`] variant, if present.",
+            self.variant
+        );
+        quote! {
+            #[doc = #doc]
+            #[must_use]
+            #vis fn #getter(&self) -> ::core::option::Option<&#data> {
+                #borrow
+            }
+
+            #[doc = #doc_mut]
+            #vis fn #getter_mut(&mut self) -> ::core::option::Option<&mut #data> {
+                #borrow_mut
+            }
+        }
+    }
+
+    /// Compares the fields bound by reference by `pattern` with the data of the variant borrowed as `__data`.
+    fn comparisons(&self) -> Vec<TokenStream> {
+        let bindings = (0..self.len).map(|index| format_ident!("__field_{}", index));
+        if self.len == 1 {
+            return bindings
+                .map(|binding| quote! { ::core::cmp::PartialEq::eq(__data, #binding) })
+                .collect();
+        }
+        bindings
+            .enumerate()
+            .map(|(index, binding)| {
+                let index = syn::Index::from(index);
+                quote! { ::core::cmp::PartialEq::eq(&__data.#index, #binding) }
+            })
+            .collect()
+    }
+
+    /// The match arm of `set`, storing a value of the variant and returning the replaced one.
+    fn set_case(&self) -> TokenStream {
+        let Self {
+            field,
+            pattern,
+            pack,
+            unpack,
+            ..
+        } = self;
+        quote! {
+            #pattern => self.#field.replace(#pack).map(#unpack),
+        }
+    }
+
+    /// The match arm of `remove`, taking the value of the variant out of its field.
+    fn take_case(&self, variants_enum_name: &Ident) -> TokenStream {
+        let Self {
+            variant,
+            field,
+            unpack,
+            ..
+        } = self;
+        quote! {
+            #variants_enum_name::#variant => self.#field.take().map(#unpack),
+        }
+    }
+}
+
+/// Converts a name to snake case, such as `HttpRequest` to `http_request`, for the fields and modules named after
+/// variants and enums.
+pub(crate) fn snake_case(ident: &Ident) -> Ident {
+    let name = ident.to_string();
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+
+    for (index, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+
+    syn::parse_str::<Ident>(&snake).unwrap_or_else(|_| Ident::new_raw(&snake, Span::call_site()))
+}
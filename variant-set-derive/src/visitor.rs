@@ -0,0 +1,158 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Fields, Member, Variant, Visibility};
+
+use crate::{model::KeyVariant, set::snake_case};
+
+/// Generates the visitor traits requested with `#[variant_enum(visitor)]`, `{Name}Visitor` and `{Name}VisitorMut`,
+/// with one method per variant of the derived enum, and implements `VariantVisit` and `VariantVisitMut` for every
+/// type implementing them.
+///
+/// The methods receive the fields of the variant in declaration order, including key fields, which `{Name}VisitorMut`
+/// only receives by shared reference since the value is stored under its key. Variants in a slot have their own method.
+pub(crate) fn expand_visitor(
+    vis: &Visibility,
+    name: &Ident,
+    key_variants: &[KeyVariant],
+) -> TokenStream {
+    let visitor_name = format_ident!("{}Visitor", name);
+    let visitor_mut_name = format_ident!("{}VisitorMut", name);
+    let methods = key_variants
+        .iter()
+        .flat_map(|key| {
+            key.sources
+                .iter()
+                .map(move |source| VisitMethod::new(source, key))
+        })
+        .collect::<Vec<_>>();
+
+    let declarations = methods.iter().map(|method| method.declaration(name, false));
+    let declarations_mut = methods.iter().map(|method| method.declaration(name, true));
+    let cases = methods
+        .iter()
+        .map(|method| method.case(name, &visitor_name))
+        .collect::<Vec<_>>();
+    let cases_mut = methods
+        .iter()
+        .map(|method| method.case(name, &visitor_mut_name));
+
+    let visitor_doc = format!(
+        "A visitor of [`{name}`] values, with one method per variant receiving references to its fields.\n\nValues \
+         are passed to it with `VariantVisit::accept` or `VariantSet::visit`."
+    );
+    let visitor_mut_doc = format!(
+        "A visitor of [`{name}`] values, with one method per variant receiving mutable references to its fields.\n\n\
+         Key fields are only received by shared reference, since changing them would leave the value stored under its \
+         previous key. Values are passed to it with `VariantVisitMut::accept_mut` or `VariantSet::visit_mut`."
+    );
+
+    quote! {
+        #[doc = #visitor_doc]
+        #vis trait #visitor_name {
+            #(#declarations)*
+        }
+
+        #[doc = #visitor_mut_doc]
+        #vis trait #visitor_mut_name {
+            #(#declarations_mut)*
+        }
+
+        #[allow(deprecated)]
+        impl<__V> ::variant_set::VariantVisit<__V> for #name
+        where
+            __V: #visitor_name + ?::core::marker::Sized,
+        {
+            fn accept(&self, visitor: &mut __V) {
+                match self {
+                    #(#cases)*
+                }
+            }
+        }
+
+        #[allow(deprecated)]
+        impl<__V> ::variant_set::VariantVisitMut<__V> for #name
+        where
+            __V: #visitor_mut_name + ?::core::marker::Sized,
+        {
+            fn accept_mut(&mut self, visitor: &mut __V) {
+                match self {
+                    #(#cases_mut)*
+                }
+            }
+        }
+    }
+}
+
+/// The method of the visitor traits for a variant of the derived enum.
+struct VisitMethod<'a> {
+    variant: &'a Variant,
+    method: Ident,
+    /// The names of the parameters: the field names, or the variant name in snake case for tuple fields.
+    params: Vec<Ident>,
+    /// The fields marked with `#[variant(key)]`, which are never passed by mutable reference.
+    key_members: Vec<&'a Member>,
+}
+
+impl<'a> VisitMethod<'a> {
+    fn new(variant: &'a Variant, key: &'a KeyVariant) -> Self {
+        let snake = snake_case(&variant.ident);
+        let params = match &variant.fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .filter_map(|field| field.ident.clone())
+                .collect(),
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => vec![snake.clone()],
+            Fields::Unnamed(fields) => (0..fields.unnamed.len())
+                .map(|index| format_ident!("{}_{}", snake, index))
+                .collect(),
+            Fields::Unit => Vec::new(),
+        };
+
+        Self {
+            variant,
+            method: format_ident!("visit_{}", snake),
+            params,
+            key_members: key.fields.iter().map(|(member, _)| member).collect(),
+        }
+    }
+
+    /// The declaration of the method, taking the fields by `&`, or by `&mut` if `mutable` except for key fields.
+    fn declaration(&self, name: &Ident, mutable: bool) -> TokenStream {
+        let method = &self.method;
+        let params = &self.params;
+        let types = self
+            .variant
+            .fields
+            .iter()
+            .zip(self.variant.fields.members())
+            .map(|(field, member)| {
+                let ty = &field.ty;
+                if mutable && !self.key_members.contains(&&member) {
+                    quote! { &mut #ty }
+                } else {
+                    quote! { &#ty }
+                }
+            });
+        let doc = format!("Visits a [`{name}::{}`] value.", self.variant.ident);
+        quote! {
+            #[doc = #doc]
+            fn #method(&mut self, #(#params: #types),*);
+        }
+    }
+
+    /// The match arm of `accept` or `accept_mut`, calling the method of the given visitor trait.
+    fn case(&self, name: &Ident, visitor_name: &Ident) -> TokenStream {
+        let variant_name = &self.variant.ident;
+        let method = &self.method;
+        let members = self.variant.fields.members();
+        let bindings = (0..self.params.len())
+            .map(|index| format_ident!("__field_{}", index))
+            .collect::<Vec<_>>();
+        quote! {
+            #name::#variant_name { #(#members: #bindings),* } => {
+                <__V as #visitor_name>::#method(visitor, #(#bindings),*);
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Compares `VariantSet` with `SparseVariantSet` for an enum with 300 variants, of which a few are present.
+//!
+//! Run with `cargo bench --bench storage`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use variant_set::{DenseIndex, FiniteKey, SparseVariantSet, VariantEnum, VariantKey, VariantSet};
+
+const VARIANTS: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Id(u16);
+
+impl VariantKey for Id {
+    type BuildHasher = DenseIndex;
+}
+
+impl FiniteKey for Id {
+    const COUNT: usize = VARIANTS;
+    const ALL: &'static [Self] = &{
+        let mut all = [Id(0); VARIANTS];
+        let mut index = 0;
+        while index < VARIANTS {
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                all[index] = Id(index as u16);
+            }
+            index += 1;
+        }
+        all
+    };
+
+    fn index(self) -> usize {
+        usize::from(self.0)
+    }
+
+    fn name(self) -> &'static str {
+        "Id"
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Message {
+    id: Id,
+    payload: u64,
+}
+
+impl VariantEnum for Message {
+    type Variant = Id;
+
+    fn variant(&self) -> Id {
+        self.id
+    }
+}
+
+fn messages(present: usize) -> Vec<Message> {
+    (0..present)
+        .map(|n| Message {
+            id: Id(u16::try_from(n * 97 % VARIANTS).unwrap()),
+            payload: n as u64,
+        })
+        .collect()
+}
+
+/// Runs `f` repeatedly for about half a second, and returns the mean duration of a run.
+fn measure(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < Duration::from_millis(500) {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn main() {
+    for present in [4, 32, 150] {
+        let values = messages(present);
+        let lookups = (0..VARIANTS)
+            .map(|n| Id(u16::try_from(n).unwrap()))
+            .collect::<Vec<_>>();
+
+        let build_map = measure(|| {
+            black_box(values.iter().cloned().collect::<VariantSet<_>>());
+        });
+        let build_sparse = measure(|| {
+            black_box(values.iter().cloned().collect::<SparseVariantSet<_>>());
+        });
+
+        let map = values.iter().cloned().collect::<VariantSet<_>>();
+        let sparse = values.iter().cloned().collect::<SparseVariantSet<_>>();
+        let lookup_map = measure(|| {
+            for id in &lookups {
+                black_box(map.get(*id));
+            }
+        });
+        let lookup_sparse = measure(|| {
+            for id in &lookups {
+                black_box(sparse.get(*id));
+            }
+        });
+        let iter_map = measure(|| {
+            black_box(map.iter().map(|message| message.payload).sum::<u64>());
+        });
+        let iter_sparse = measure(|| {
+            black_box(sparse.iter().map(|message| message.payload).sum::<u64>());
+        });
+
+        println!(
+            "{present} of {VARIANTS} variants present (sparse set is dense: {})",
+            sparse.storage().is_dense()
+        );
+        println!("  build:            VariantSet {build_map:>10.2?}  SparseVariantSet {build_sparse:>10.2?}");
+        println!("  {VARIANTS} lookups:      VariantSet {lookup_map:>10.2?}  SparseVariantSet {lookup_sparse:>10.2?}");
+        println!("  iterate:          VariantSet {iter_map:>10.2?}  SparseVariantSet {iter_sparse:>10.2?}");
+    }
+}
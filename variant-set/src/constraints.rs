@@ -0,0 +1,429 @@
+use std::{iter, ops::Deref};
+
+use crate::{ConstraintViolation, VariantEnum, VariantKey, VariantSet, VariantStorage};
+
+/// A trait for variant enums with constraints between their variants, declared with `#[variant(requires(...))]` and
+/// `#[variant(conflicts_with(...))]`.
+///
+/// `#[derive(VariantEnum)]` implements this trait for the variant enum when at least one variant declares a
+/// constraint. Variants with key fields cannot declare constraints.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantConstraints, VariantEnum};
+///
+/// #[derive(VariantEnum)]
+/// enum Setting {
+///     #[variant(requires(TlsKey))]
+///     TlsCert(String),
+///     TlsKey(String),
+///     #[variant(conflicts_with(ProxyUrl))]
+///     Offline,
+///     ProxyUrl(String),
+/// }
+///
+/// assert_eq!(SettingVariant::TlsCert.requires(), [SettingVariant::TlsKey]);
+/// assert_eq!(SettingVariant::Offline.conflicts_with(), [SettingVariant::ProxyUrl]);
+/// assert!(SettingVariant::ProxyUrl.conflicts_with().is_empty());
+/// ```
+pub trait VariantConstraints: VariantKey + 'static {
+    /// Returns the variants that must be present in a set containing this variant.
+    fn requires(self) -> &'static [Self];
+
+    /// Returns the variants that must not be present in a set containing this variant.
+    fn conflicts_with(self) -> &'static [Self];
+}
+
+impl<T, S> VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    /// Checks that the set satisfies the constraints declared between its variants.
+    ///
+    /// # Errors
+    /// Returns every violated constraint, in the order of the variants declaring them in `iter`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{ConstraintViolation, VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Setting {
+    ///     #[variant(requires(TlsKey))]
+    ///     TlsCert(String),
+    ///     TlsKey(String),
+    ///     #[variant(conflicts_with(ProxyUrl))]
+    ///     Offline,
+    ///     ProxyUrl(String),
+    /// }
+    ///
+    /// let mut set = VariantSet::from([Setting::TlsCert("cert.pem".to_string())]);
+    /// assert_eq!(
+    ///     set.validate(),
+    ///     Err(vec![ConstraintViolation::Missing {
+    ///         variant: SettingVariant::TlsCert,
+    ///         required: SettingVariant::TlsKey,
+    ///     }]),
+    /// );
+    ///
+    /// set.set(Setting::TlsKey("key.pem".to_string()));
+    /// assert_eq!(set.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ConstraintViolation<T::Variant>>>
+    where
+        T::Variant: VariantConstraints,
+    {
+        into_result(violations(self.variants(), |variant| {
+            self.contains(variant)
+        }))
+    }
+}
+
+/// Returns the constraints of the `present` variants that are violated, with `contains` telling which variants are
+/// present.
+fn violations<V>(
+    present: impl Iterator<Item = V>,
+    contains: impl Fn(V) -> bool,
+) -> Vec<ConstraintViolation<V>>
+where
+    V: VariantConstraints,
+{
+    let mut violations = Vec::new();
+
+    for variant in present {
+        for &required in variant.requires() {
+            if !contains(required) {
+                violations.push(ConstraintViolation::Missing { variant, required });
+            }
+        }
+        for &conflicting in variant.conflicts_with() {
+            if contains(conflicting) {
+                violations.push(ConstraintViolation::Conflict {
+                    variant,
+                    conflicting,
+                });
+            }
+        }
+    }
+
+    violations
+}
+
+/// Returns the violations as an error, if there are any.
+fn into_result<V>(
+    violations: Vec<ConstraintViolation<V>>,
+) -> Result<(), Vec<ConstraintViolation<V>>> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// A `VariantSet` that always satisfies the constraints declared between its variants, rejecting the changes that
+/// would violate them.
+///
+/// All read-only methods of `VariantSet` are available through `Deref`. Methods that modify the set are provided by
+/// `ValidatedVariantSet` itself, and return the violated constraints instead of applying a change that breaks them.
+/// Since values are added and removed one at a time, a value can only be added once the values it requires are
+/// present, and removed once no value requiring it is left. Variants requiring each other can be added together by
+/// converting a valid `VariantSet` with `TryFrom`.
+///
+/// # Examples
+/// ```
+/// use variant_set::{ConstraintViolation, ValidatedVariantSet, VariantEnum};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Setting {
+///     #[variant(requires(TlsKey))]
+///     TlsCert(String),
+///     TlsKey(String),
+///     #[variant(conflicts_with(ProxyUrl))]
+///     Offline,
+///     ProxyUrl(String),
+/// }
+///
+/// let mut set = ValidatedVariantSet::new();
+/// assert!(set.set(Setting::TlsCert("cert.pem".to_string())).is_err());
+///
+/// set.set(Setting::TlsKey("key.pem".to_string())).unwrap();
+/// set.set(Setting::TlsCert("cert.pem".to_string())).unwrap();
+/// assert_eq!(set.len(), 2);
+///
+/// set.set(Setting::ProxyUrl("http://proxy:3128".to_string())).unwrap();
+/// assert_eq!(
+///     set.set(Setting::Offline),
+///     Err(vec![ConstraintViolation::Conflict {
+///         variant: SettingVariant::Offline,
+///         conflicting: SettingVariant::ProxyUrl,
+///     }]),
+/// );
+/// assert!(!set.contains(SettingVariant::Offline));
+/// ```
+pub struct ValidatedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    set: VariantSet<T>,
+}
+
+impl<T> ValidatedVariantSet<T>
+where
+    T: VariantEnum,
+    T::Variant: VariantConstraints,
+{
+    /// Creates a new, empty `ValidatedVariantSet`, which satisfies any constraint.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{ValidatedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Setting {
+    ///     #[variant(requires(TlsKey))]
+    ///     TlsCert(String),
+    ///     TlsKey(String),
+    /// }
+    ///
+    /// let set: ValidatedVariantSet<Setting> = ValidatedVariantSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            set: VariantSet::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying `VariantSet`.
+    #[must_use]
+    pub fn as_set(&self) -> &VariantSet<T> {
+        &self.set
+    }
+
+    /// Consumes the validated set and returns the underlying `VariantSet`.
+    #[must_use]
+    pub fn into_inner(self) -> VariantSet<T> {
+        self.set
+    }
+
+    /// Adds a value to the set if its variant is not present yet and adding it satisfies the constraints. Returns
+    /// whether the value was inserted. See `VariantSet::insert`.
+    ///
+    /// # Errors
+    /// Returns the violated constraints if the variant is not present yet and adding it would violate them.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{ValidatedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Setting {
+    ///     #[variant(conflicts_with(ProxyUrl))]
+    ///     Offline,
+    ///     ProxyUrl(String),
+    /// }
+    ///
+    /// let mut set = ValidatedVariantSet::new();
+    /// assert_eq!(set.insert(Setting::Offline), Ok(true));
+    /// assert_eq!(set.insert(Setting::Offline), Ok(false));
+    /// assert!(set.insert(Setting::ProxyUrl("http://proxy:3128".to_string())).is_err());
+    /// ```
+    pub fn insert(&mut self, value: T) -> Result<bool, Vec<ConstraintViolation<T::Variant>>> {
+        if self.set.contains(value.variant()) {
+            return Ok(false);
+        }
+
+        self.check_added(value.variant())?;
+        Ok(self.set.insert(value))
+    }
+
+    /// Adds a value to the set, replacing the value of the same variant, if it satisfies the constraints. Returns the
+    /// replaced value. See `VariantSet::set`.
+    ///
+    /// Replacing a value with another of the same variant is always allowed, since constraints are between variants.
+    ///
+    /// # Errors
+    /// Returns the violated constraints if the variant is not present yet and adding it would violate them.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{ConstraintViolation, ValidatedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Setting {
+    ///     #[variant(requires(TlsKey))]
+    ///     TlsCert(String),
+    ///     TlsKey(String),
+    /// }
+    ///
+    /// let mut set = ValidatedVariantSet::new();
+    /// assert_eq!(
+    ///     set.set(Setting::TlsCert("cert.pem".to_string())),
+    ///     Err(vec![ConstraintViolation::Missing {
+    ///         variant: SettingVariant::TlsCert,
+    ///         required: SettingVariant::TlsKey,
+    ///     }]),
+    /// );
+    ///
+    /// assert_eq!(set.set(Setting::TlsKey("old.pem".to_string())), Ok(None));
+    /// assert_eq!(
+    ///     set.set(Setting::TlsKey("new.pem".to_string())),
+    ///     Ok(Some(Setting::TlsKey("old.pem".to_string()))),
+    /// );
+    /// ```
+    pub fn set(&mut self, value: T) -> Result<Option<T>, Vec<ConstraintViolation<T::Variant>>> {
+        if !self.set.contains(value.variant()) {
+            self.check_added(value.variant())?;
+        }
+
+        Ok(self.set.set(value))
+    }
+
+    /// Removes the value of a variant from the set if removing it satisfies the constraints. Returns the value if it
+    /// existed. See `VariantSet::remove`.
+    ///
+    /// # Errors
+    /// Returns the violated constraints if the variant is present and removing it would violate them.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{ConstraintViolation, ValidatedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Setting {
+    ///     #[variant(requires(TlsKey))]
+    ///     TlsCert(String),
+    ///     TlsKey(String),
+    /// }
+    ///
+    /// let mut set = ValidatedVariantSet::new();
+    /// set.set(Setting::TlsKey("key.pem".to_string())).unwrap();
+    /// set.set(Setting::TlsCert("cert.pem".to_string())).unwrap();
+    ///
+    /// assert!(set.remove(SettingVariant::TlsKey).is_err());
+    /// assert_eq!(set.remove(SettingVariant::TlsCert), Ok(Some(Setting::TlsCert("cert.pem".to_string()))));
+    /// assert_eq!(set.remove(SettingVariant::TlsKey), Ok(Some(Setting::TlsKey("key.pem".to_string()))));
+    /// assert_eq!(set.remove(SettingVariant::TlsKey), Ok(None));
+    /// ```
+    pub fn remove(
+        &mut self,
+        variant: T::Variant,
+    ) -> Result<Option<T>, Vec<ConstraintViolation<T::Variant>>> {
+        if !self.set.contains(variant) {
+            return Ok(None);
+        }
+
+        let remaining = self.set.variants().filter(|present| *present != variant);
+        into_result(violations(remaining, |required| {
+            required != variant && self.set.contains(required)
+        }))?;
+        Ok(self.set.remove(variant))
+    }
+
+    /// Removes and returns the value of a variant, if removing it satisfies the constraints. See `remove`.
+    ///
+    /// # Errors
+    /// Returns the violated constraints if the variant is present and removing it would violate them.
+    pub fn take(
+        &mut self,
+        variant: T::Variant,
+    ) -> Result<Option<T>, Vec<ConstraintViolation<T::Variant>>> {
+        self.remove(variant)
+    }
+
+    /// Clears the set, removing all values. An empty set satisfies any constraint.
+    pub fn clear(&mut self) {
+        self.set.clear();
+    }
+
+    /// Checks the constraints of the set with a value of a variant that is not present yet added to it.
+    fn check_added(&self, added: T::Variant) -> Result<(), Vec<ConstraintViolation<T::Variant>>> {
+        let present = self.set.variants().chain(iter::once(added));
+        into_result(violations(present, |variant| {
+            variant == added || self.set.contains(variant)
+        }))
+    }
+}
+
+impl<T> Default for ValidatedVariantSet<T>
+where
+    T: VariantEnum,
+    T::Variant: VariantConstraints,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for ValidatedVariantSet<T>
+where
+    T: VariantEnum + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            set: self.set.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ValidatedVariantSet<T>
+where
+    T: VariantEnum + std::fmt::Debug,
+    T::Variant: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidatedVariantSet")
+            .field("set", &self.set)
+            .finish()
+    }
+}
+
+impl<T> Deref for ValidatedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    type Target = VariantSet<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.set
+    }
+}
+
+impl<T> TryFrom<VariantSet<T>> for ValidatedVariantSet<T>
+where
+    T: VariantEnum,
+    T::Variant: VariantConstraints,
+{
+    type Error = Vec<ConstraintViolation<T::Variant>>;
+
+    /// Validates a set, and wraps it if it satisfies the constraints.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{ValidatedVariantSet, VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Setting {
+    ///     #[variant(requires(TlsKey))]
+    ///     TlsCert(String),
+    ///     #[variant(requires(TlsCert))]
+    ///     TlsKey(String),
+    /// }
+    ///
+    /// let set = VariantSet::from([Setting::TlsCert("cert.pem".to_string())]);
+    /// assert!(ValidatedVariantSet::try_from(set).is_err());
+    ///
+    /// let set = VariantSet::from([
+    ///     Setting::TlsCert("cert.pem".to_string()),
+    ///     Setting::TlsKey("key.pem".to_string()),
+    /// ]);
+    /// let set = ValidatedVariantSet::try_from(set).unwrap();
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    fn try_from(set: VariantSet<T>) -> Result<Self, Self::Error> {
+        set.validate()?;
+        Ok(Self { set })
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{VariantEnum, VariantSet, VariantStorage};
+
+/// The differences between two `VariantSet`s, as returned by `VariantSet::diff`.
+///
+/// Applying the diff to the first set with `VariantSet::apply` turns it into the second set. With the `serde` feature
+/// enabled, and `#[variant_enum(serde)]` on the enum, the diff can be serialized and sent elsewhere to be applied to a
+/// copy of the first set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "T: serde::Serialize, T::Variant: serde::Serialize",
+        deserialize = "T: serde::Deserialize<'de>, T::Variant: serde::Deserialize<'de>"
+    ))
+)]
+pub struct VariantSetDiff<T>
+where
+    T: VariantEnum,
+{
+    /// Variants that are only present in the first set.
+    pub removed: Vec<T::Variant>,
+
+    /// Values whose variant is only present in the second set.
+    pub added: Vec<T>,
+
+    /// Values from the second set whose variant is present in both sets, but with a different value.
+    pub changed: Vec<T>,
+}
+
+impl<T> VariantSetDiff<T>
+where
+    T: VariantEnum,
+{
+    /// Returns `true` if the two compared sets were equal.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Clone, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let set = VariantSet::from([MyEnum::Variant2(42)]);
+    /// assert!(set.diff(&set.clone()).is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl<T> Default for VariantSetDiff<T>
+where
+    T: VariantEnum,
+{
+    fn default() -> Self {
+        Self {
+            removed: Vec::new(),
+            added: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+}
+
+impl<T, S> VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    /// Computes the differences between this set and `other`.
+    ///
+    /// The returned diff lists the variants only present in this set, the values only present in `other`, and the
+    /// values of `other` whose variant is present in both sets but whose value is not equal to the one in this set.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, Clone, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    ///     Variant3(bool),
+    /// }
+    ///
+    /// let a = VariantSet::from([MyEnum::Variant1("Hello".to_string()), MyEnum::Variant2(42)]);
+    /// let b = VariantSet::from([MyEnum::Variant2(10), MyEnum::Variant3(true)]);
+    ///
+    /// let diff = a.diff(&b);
+    /// assert_eq!(diff.removed, vec![MyEnumVariant::Variant1]);
+    /// assert_eq!(diff.added, vec![MyEnum::Variant3(true)]);
+    /// assert_eq!(diff.changed, vec![MyEnum::Variant2(10)]);
+    /// ```
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> VariantSetDiff<T>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut diff = VariantSetDiff::default();
+
+        for (variant, value) in self.iter_pairs() {
+            match other.get(variant) {
+                None => diff.removed.push(variant),
+                Some(other_value) if other_value != value => diff.changed.push(other_value.clone()),
+                Some(_) => {}
+            }
+        }
+
+        for (variant, value) in other.iter_pairs() {
+            if !self.contains(variant) {
+                diff.added.push(value.clone());
+            }
+        }
+
+        diff
+    }
+
+    /// Applies a diff computed by `VariantSet::diff`. If this set is equal to the first set passed to `diff`, it will
+    /// be equal to the second set afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, Clone, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    ///     Variant3(bool),
+    /// }
+    ///
+    /// let a = VariantSet::from([MyEnum::Variant1("Hello".to_string()), MyEnum::Variant2(42)]);
+    /// let b = VariantSet::from([MyEnum::Variant2(10), MyEnum::Variant3(true)]);
+    ///
+    /// let mut patched = a.clone();
+    /// patched.apply(a.diff(&b));
+    /// assert_eq!(patched, b);
+    /// ```
+    ///
+    /// With the `serde` feature enabled, diffs of enums deriving `serde` traits for their variants with
+    /// `#[variant_enum(serde)]` can be serialized:
+    /// ```
+    /// # #[cfg(feature = "serde")]
+    /// # {
+    /// use variant_set::{VariantSet, VariantSetDiff, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+    /// #[variant_enum(serde)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let a = VariantSet::from([MyEnum::Variant1("Hello".to_string())]);
+    /// let b = VariantSet::from([MyEnum::Variant2(42)]);
+    ///
+    /// let json = serde_json::to_string(&a.diff(&b)).unwrap();
+    /// let diff: VariantSetDiff<MyEnum> = serde_json::from_str(&json).unwrap();
+    ///
+    /// let mut patched = a.clone();
+    /// patched.apply(diff);
+    /// assert_eq!(patched, b);
+    /// # }
+    /// ```
+    pub fn apply(&mut self, diff: VariantSetDiff<T>) {
+        for variant in diff.removed {
+            self.remove(variant);
+        }
+
+        self.extend(diff.added);
+        self.extend(diff.changed);
+    }
+}
@@ -0,0 +1,244 @@
+//! A compact binary encoding of `VariantSet`s, keyed by stable variant IDs.
+//!
+//! The encoding is laid out as follows, where lengths are unsigned LEB128 integers:
+//!
+//! - the version of the encoding, as a single byte;
+//! - the length of the presence bitmap, in bytes;
+//! - the presence bitmap, in which bit `id % 8` of byte `id / 8` is set for each ID present in the set;
+//! - for each present ID in increasing order, the length of its payload followed by the payload itself.
+//!
+//! Since every payload is prefixed by its length, IDs that are unknown to the decoding side are skipped, so that sets
+//! written by a newer version of an enum can still be read by an older one.
+
+use crate::{DecodeError, VariantEnum, VariantKey, VariantSet, VariantStorage};
+
+/// The version of the encoding written by `VariantSet::encode`.
+const VERSION: u8 = 1;
+
+/// A trait for variant keys with a stable numeric ID, which does not change when variants are reordered or renamed.
+///
+/// `#[derive(VariantEnum)]` implements this trait when each variant is given an ID with `#[variant(id = ...)]`. Two
+/// variants with the same ID, or IDs above 65535, are rejected at compile time. Implementations by hand should keep
+/// IDs as small, since `VariantSet::encode` writes a bitmap with one bit per ID up to the largest one present.
+///
+/// # Examples
+/// ```
+/// use variant_set::{StableId, VariantEnum};
+///
+/// #[derive(VariantEnum)]
+/// #[cfg_attr(feature = "serde", variant_enum(serde))]
+/// enum Shape {
+///     #[variant(id = 2)]
+///     Circle { radius: f64 },
+///     #[variant(id = 1, renamed_from = "Rect")]
+///     Rectangle { width: f64, height: f64 },
+/// }
+///
+/// assert_eq!(ShapeVariant::Rectangle.id(), 1);
+/// assert_eq!(ShapeVariant::from_id(2), Some(ShapeVariant::Circle));
+/// assert_eq!(ShapeVariant::from_id(3), None);
+///
+/// // The previous name is still accepted when deserializing.
+/// # #[cfg(feature = "serde")]
+/// assert_eq!(serde_json::from_str::<ShapeVariant>("\"Rect\"").unwrap(), ShapeVariant::Rectangle);
+/// ```
+pub trait StableId: VariantKey {
+    /// Returns the ID of this key.
+    fn id(self) -> u32;
+
+    /// Returns the key with the given ID, or `None` if there is no such key.
+    fn from_id(id: u32) -> Option<Self>;
+}
+
+/// A trait for enums whose values can be written to the binary encoding of `VariantSet::encode`.
+///
+/// Only the payload of each value is encoded by this trait, its variant is stored as a `StableId`.
+pub trait EncodeVariant: VariantEnum + Sized {
+    /// Appends the data of this value to `buf`.
+    fn encode_payload(&self, buf: &mut Vec<u8>);
+
+    /// Decodes a value of the given variant from the data written by `encode_payload`.
+    ///
+    /// # Errors
+    /// Returns `DecodeError::InvalidPayload` if the payload is not valid for the variant.
+    fn decode_payload(variant: Self::Variant, payload: &[u8]) -> Result<Self, DecodeError>;
+}
+
+impl<T, S> VariantSet<T, S>
+where
+    T: EncodeVariant,
+    T::Variant: StableId,
+    S: VariantStorage<T>,
+{
+    /// Encodes the set into a compact binary format, keyed by the stable IDs of its variants.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{DecodeError, EncodeVariant, VariantEnum, VariantSet};
+    ///
+    /// mod v1 {
+    ///     #[derive(variant_set::VariantEnum, Debug, PartialEq)]
+    ///     pub enum Setting {
+    ///         #[variant(id = 1)]
+    ///         Volume(u8),
+    ///         #[variant(id = 2)]
+    ///         Muted,
+    ///     }
+    /// }
+    ///
+    /// mod v2 {
+    ///     #[derive(variant_set::VariantEnum, Debug, PartialEq)]
+    ///     pub enum Setting {
+    ///         #[variant(id = 3)]
+    ///         Balance(u8),
+    ///         #[variant(id = 2)]
+    ///         Muted,
+    ///         #[variant(id = 1)]
+    ///         Volume(u8),
+    ///     }
+    /// }
+    ///
+    /// impl EncodeVariant for v1::Setting {
+    ///     fn encode_payload(&self, buf: &mut Vec<u8>) {
+    ///         if let Self::Volume(volume) = self {
+    ///             buf.push(*volume);
+    ///         }
+    ///     }
+    ///
+    ///     fn decode_payload(variant: v1::SettingVariant, payload: &[u8]) -> Result<Self, DecodeError> {
+    ///         match (variant, payload) {
+    ///             (v1::SettingVariant::Volume, [volume]) => Ok(Self::Volume(*volume)),
+    ///             (v1::SettingVariant::Muted, []) => Ok(Self::Muted),
+    ///             _ => Err(DecodeError::InvalidPayload),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl EncodeVariant for v2::Setting {
+    ///     fn encode_payload(&self, buf: &mut Vec<u8>) {
+    ///         if let Self::Volume(value) | Self::Balance(value) = self {
+    ///             buf.push(*value);
+    ///         }
+    ///     }
+    ///
+    ///     fn decode_payload(variant: v2::SettingVariant, payload: &[u8]) -> Result<Self, DecodeError> {
+    ///         match (variant, payload) {
+    ///             (v2::SettingVariant::Volume, [volume]) => Ok(Self::Volume(*volume)),
+    ///             (v2::SettingVariant::Balance, [balance]) => Ok(Self::Balance(*balance)),
+    ///             (v2::SettingVariant::Muted, []) => Ok(Self::Muted),
+    ///             _ => Err(DecodeError::InvalidPayload),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// // A newer version of the enum, with reordered variants and a new one.
+    /// let set = VariantSet::from([v2::Setting::Balance(50), v2::Setting::Volume(80)]);
+    /// let bytes = set.encode();
+    ///
+    /// // The older version skips the variant it does not know.
+    /// let decoded: VariantSet<v1::Setting> = VariantSet::decode(&bytes).unwrap();
+    /// assert_eq!(decoded, VariantSet::from([v1::Setting::Volume(80)]));
+    ///
+    /// assert_eq!(VariantSet::<v2::Setting>::decode(&bytes), Ok(set));
+    /// ```
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut entries = self
+            .iter_pairs()
+            .map(|(variant, value)| (variant.id(), value))
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(id, _)| *id);
+
+        let bitmap_len = entries.last().map_or(0, |(id, _)| *id as usize / 8 + 1);
+        let mut bitmap = vec![0; bitmap_len];
+        for (id, _) in &entries {
+            bitmap[*id as usize / 8] |= 1 << (id % 8);
+        }
+
+        let mut buf = vec![VERSION];
+        write_len(&mut buf, bitmap_len);
+        buf.extend_from_slice(&bitmap);
+
+        let mut payload = Vec::new();
+        for (_, value) in entries {
+            payload.clear();
+            value.encode_payload(&mut payload);
+            write_len(&mut buf, payload.len());
+            buf.extend_from_slice(&payload);
+        }
+
+        buf
+    }
+
+    /// Decodes a set written by `VariantSet::encode`.
+    ///
+    /// Values whose ID is not known by the variant key are skipped, so that sets written by newer versions of the enum
+    /// can be decoded.
+    ///
+    /// # Errors
+    /// Returns a `DecodeError` if the input is not a valid encoding, or if a payload cannot be decoded.
+    pub fn decode(mut bytes: &[u8]) -> Result<Self, DecodeError>
+    where
+        S: Default,
+    {
+        let version = read_bytes(&mut bytes, 1)?[0];
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let bitmap_len = read_len(&mut bytes)?;
+        let bitmap = read_bytes(&mut bytes, bitmap_len)?;
+
+        let mut set = Self::default();
+        for (index, byte) in bitmap.iter().enumerate() {
+            for bit in (0..8).filter(|bit| byte & (1 << bit) != 0) {
+                let len = read_len(&mut bytes)?;
+                let payload = read_bytes(&mut bytes, len)?;
+
+                let id = u32::try_from(index * 8 + bit).map_err(|_| DecodeError::InvalidLength)?;
+                if let Some(variant) = T::Variant::from_id(id) {
+                    set.set(T::decode_payload(variant, payload)?);
+                }
+            }
+        }
+
+        if bytes.is_empty() {
+            Ok(set)
+        } else {
+            Err(DecodeError::TrailingBytes)
+        }
+    }
+}
+
+/// Appends `len` to `buf` as an unsigned LEB128 integer.
+#[allow(clippy::cast_possible_truncation)] // Each byte only keeps the 7 lowest bits.
+fn write_len(buf: &mut Vec<u8>, mut len: usize) {
+    while len >= 0x80 {
+        buf.push((len & 0x7f) as u8 | 0x80);
+        len >>= 7;
+    }
+    buf.push(len as u8);
+}
+
+/// Reads an unsigned LEB128 integer from the start of `bytes`, and advances past it.
+fn read_len(bytes: &mut &[u8]) -> Result<usize, DecodeError> {
+    let mut len = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let byte = read_bytes(bytes, 1)?[0];
+        len |= usize::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(len);
+        }
+    }
+    Err(DecodeError::InvalidLength)
+}
+
+/// Reads `len` bytes from the start of `bytes`, and advances past them.
+fn read_bytes<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], DecodeError> {
+    if bytes.len() < len {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    let (read, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(read)
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+/// The error type for `VariantSet::decode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The input ended in the middle of the encoded set.
+    UnexpectedEnd,
+    /// The input was encoded with a newer, unsupported version of the encoding.
+    UnsupportedVersion(u8),
+    /// The input continues after the end of the encoded set.
+    TrailingBytes,
+    /// A length or ID in the input is too large.
+    InvalidLength,
+    /// The payload of a value could not be decoded by `EncodeVariant::decode_payload`.
+    InvalidPayload,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported encoding version {version}")
+            }
+            Self::TrailingBytes => f.write_str("unexpected bytes after the end of the encoded set"),
+            Self::InvalidLength => f.write_str("length or ID too large"),
+            Self::InvalidPayload => f.write_str("invalid payload"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// The error type for `VariantTransitions::check`, for a change of variant that is not an allowed transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvalidTransition<V> {
+    /// The variant of the old value.
+    pub from: V,
+    /// The variant of the new value.
+    pub to: V,
+}
+
+impl<V> fmt::Display for InvalidTransition<V>
+where
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid transition from {:?} to {:?}",
+            self.from, self.to
+        )
+    }
+}
+
+impl<V> std::error::Error for InvalidTransition<V> where V: fmt::Debug {}
+
+/// A constraint declared with `#[variant(requires(...))]` or `#[variant(conflicts_with(...))]` that a set does not
+/// satisfy, as returned by `VariantSet::validate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConstraintViolation<V> {
+    /// A variant is present, but a variant it requires is not.
+    Missing {
+        /// The variant declaring the requirement.
+        variant: V,
+        /// The required variant.
+        required: V,
+    },
+    /// A variant is present together with a variant it conflicts with.
+    Conflict {
+        /// The variant declaring the conflict.
+        variant: V,
+        /// The conflicting variant.
+        conflicting: V,
+    },
+}
+
+impl<V> fmt::Display for ConstraintViolation<V>
+where
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { variant, required } => write!(f, "{variant:?} requires {required:?}"),
+            Self::Conflict {
+                variant,
+                conflicting,
+            } => write!(f, "{variant:?} conflicts with {conflicting:?}"),
+        }
+    }
+}
+
+impl<V> std::error::Error for ConstraintViolation<V> where V: fmt::Debug {}
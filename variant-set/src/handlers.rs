@@ -0,0 +1,311 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{FiniteKey, VariantEnum, VariantHasher, VariantSet, VariantStorage};
+
+/// A boxed handler of the values of one variant.
+type Handler<T, R> = Box<dyn FnMut(&T) -> R>;
+
+/// A dispatch table calling a handler chosen by the variant of a value, instead of a `match` delegating to handlers.
+///
+/// At most one handler is registered per variant, and a fallback handler can be set for the variants without one.
+/// `unhandled` lists the variants without a handler, so that a table built at startup can be checked to be complete.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantHandlers};
+///
+/// #[derive(VariantEnum)]
+/// enum Message {
+///     Ping,
+///     Echo(String),
+///     Add(i32, i32),
+/// }
+///
+/// let mut handlers = VariantHandlers::new();
+/// handlers
+///     .register(MessageVariant::Ping, |_| "pong".to_string())
+///     .register(MessageVariant::Echo, |message| match message {
+///         Message::Echo(text) => text.clone(),
+///         _ => unreachable!(),
+///     });
+///
+/// assert_eq!(handlers.dispatch(&Message::Ping), Some("pong".to_string()));
+/// assert_eq!(handlers.dispatch(&Message::Echo("hi".to_string())), Some("hi".to_string()));
+/// assert_eq!(handlers.dispatch(&Message::Add(1, 2)), None);
+/// assert_eq!(handlers.unhandled(), [MessageVariant::Add]);
+/// ```
+pub struct VariantHandlers<T, R>
+where
+    T: VariantEnum,
+{
+    handlers: HashMap<T::Variant, Handler<T, R>, VariantHasher<T>>,
+    fallback: Option<Handler<T, R>>,
+}
+
+impl<T, R> VariantHandlers<T, R>
+where
+    T: VariantEnum,
+{
+    /// Creates an empty table, without handlers or fallback.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    /// }
+    ///
+    /// let mut handlers: VariantHandlers<Message, ()> = VariantHandlers::new();
+    /// assert_eq!(handlers.dispatch(&Message::Ping), None);
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::with_hasher(VariantHasher::<T>::default()),
+            fallback: None,
+        }
+    }
+
+    /// Registers the handler of a variant, replacing its previous handler. Returns the table, so that registrations
+    /// can be chained.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    ///     Quit,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::new();
+    /// handlers
+    ///     .register(MessageVariant::Ping, |_| 1)
+    ///     .register(MessageVariant::Quit, |_| 2)
+    ///     .register(MessageVariant::Ping, |_| 3);
+    ///
+    /// assert_eq!(handlers.dispatch(&Message::Ping), Some(3));
+    /// assert_eq!(handlers.dispatch(&Message::Quit), Some(2));
+    /// ```
+    pub fn register<F>(&mut self, variant: T::Variant, handler: F) -> &mut Self
+    where
+        F: FnMut(&T) -> R + 'static,
+    {
+        self.handlers.insert(variant, Box::new(handler));
+        self
+    }
+
+    /// Removes the handler of a variant. Returns whether the variant had a handler.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::new();
+    /// handlers.register(MessageVariant::Ping, |_| ());
+    ///
+    /// assert!(handlers.unregister(MessageVariant::Ping));
+    /// assert!(!handlers.unregister(MessageVariant::Ping));
+    /// assert_eq!(handlers.dispatch(&Message::Ping), None);
+    /// ```
+    pub fn unregister(&mut self, variant: T::Variant) -> bool {
+        self.handlers.remove(&variant).is_some()
+    }
+
+    /// Sets the handler called for the values of variants without a handler of their own, replacing the previous
+    /// fallback. Returns the table, so that it can be chained with registrations.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    ///     Echo(String),
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::new();
+    /// handlers
+    ///     .register(MessageVariant::Ping, |_| "pong")
+    ///     .set_fallback(|_| "unsupported");
+    ///
+    /// assert_eq!(handlers.dispatch(&Message::Ping), Some("pong"));
+    /// assert_eq!(handlers.dispatch(&Message::Echo("hi".to_string())), Some("unsupported"));
+    /// ```
+    pub fn set_fallback<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: FnMut(&T) -> R + 'static,
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Removes the fallback handler. Returns whether there was one.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::new();
+    /// handlers.set_fallback(|_| ());
+    ///
+    /// assert!(handlers.clear_fallback());
+    /// assert_eq!(handlers.dispatch(&Message::Ping), None);
+    /// ```
+    pub fn clear_fallback(&mut self) -> bool {
+        self.fallback.take().is_some()
+    }
+
+    /// Returns `true` if the variant has a handler of its own, regardless of the fallback.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    ///     Quit,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::<Message, ()>::new();
+    /// handlers.register(MessageVariant::Ping, |_| ()).set_fallback(|_| ());
+    ///
+    /// assert!(handlers.is_registered(MessageVariant::Ping));
+    /// assert!(!handlers.is_registered(MessageVariant::Quit));
+    /// ```
+    #[must_use]
+    pub fn is_registered(&self, variant: T::Variant) -> bool {
+        self.handlers.contains_key(&variant)
+    }
+
+    /// Calls the handler of the variant of the value, or the fallback if the variant has no handler, and returns its
+    /// result. Returns `None` if neither exists.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Add(i32, i32),
+    ///     Quit,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::new();
+    /// handlers.register(MessageVariant::Add, |message| match message {
+    ///     Message::Add(a, b) => a + b,
+    ///     _ => unreachable!(),
+    /// });
+    ///
+    /// assert_eq!(handlers.dispatch(&Message::Add(2, 3)), Some(5));
+    /// assert_eq!(handlers.dispatch(&Message::Quit), None);
+    /// ```
+    pub fn dispatch(&mut self, value: &T) -> Option<R> {
+        match self.handlers.get_mut(&value.variant()) {
+            Some(handler) => Some(handler(value)),
+            None => self.fallback.as_mut().map(|fallback| fallback(value)),
+        }
+    }
+
+    /// Dispatches every value of the set, in the same order as `iter`, and returns the results with the variant of
+    /// the value they were returned for. Values without a handler or fallback are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers, VariantSet};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Setting {
+    ///     Volume(u8),
+    ///     Theme(String),
+    ///     Muted,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::new();
+    /// handlers
+    ///     .register(SettingVariant::Volume, |_| "volume changed")
+    ///     .register(SettingVariant::Theme, |_| "theme changed");
+    ///
+    /// let set = VariantSet::from([Setting::Volume(3), Setting::Muted]);
+    ///
+    /// assert_eq!(handlers.dispatch_all(&set), [(SettingVariant::Volume, "volume changed")]);
+    /// ```
+    pub fn dispatch_all<S>(&mut self, set: &VariantSet<T, S>) -> Vec<(T::Variant, R)>
+    where
+        S: VariantStorage<T>,
+    {
+        set.iter()
+            .filter_map(|value| Some((value.variant(), self.dispatch(value)?)))
+            .collect()
+    }
+
+    /// Returns the variants without a handler of their own, in declaration order, regardless of the fallback.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantHandlers};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Message {
+    ///     Ping,
+    ///     Echo(String),
+    ///     Quit,
+    /// }
+    ///
+    /// let mut handlers = VariantHandlers::<Message, ()>::new();
+    /// handlers.register(MessageVariant::Echo, |_| ());
+    ///
+    /// assert_eq!(handlers.unhandled(), [MessageVariant::Ping, MessageVariant::Quit]);
+    ///
+    /// handlers.register(MessageVariant::Ping, |_| ()).register(MessageVariant::Quit, |_| ());
+    /// assert!(handlers.unhandled().is_empty());
+    /// ```
+    #[must_use]
+    pub fn unhandled(&self) -> Vec<T::Variant>
+    where
+        T::Variant: FiniteKey,
+    {
+        T::Variant::ALL
+            .iter()
+            .copied()
+            .filter(|variant| !self.is_registered(*variant))
+            .collect()
+    }
+}
+
+impl<T, R> Default for VariantHandlers<T, R>
+where
+    T: VariantEnum,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, R> fmt::Debug for VariantHandlers<T, R>
+where
+    T: VariantEnum,
+    T::Variant: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VariantHandlers")
+            .field("registered", &self.handlers.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
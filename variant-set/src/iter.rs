@@ -0,0 +1,906 @@
+use std::{fmt, iter::FusedIterator, slice, vec};
+
+use crate::{
+    DefaultStorage, FiniteKey, VariantEnum, VariantInfo, VariantKind, VariantReflect,
+    VariantStorage,
+};
+
+/// An iterator over the values of a `VariantSet`.
+///
+/// This `struct` is created by `VariantSet::iter`, or by iterating over a `&VariantSet`.
+///
+/// All iterators of `VariantSet` know their exact length, and can be printed with `Debug` to show the remaining items.
+///
+/// # Examples
+/// ```
+/// use variant_set::{Iter, VariantSet, VariantEnum};
+///
+/// #[derive(VariantEnum, Debug)]
+/// enum MyEnum {
+///     Variant1(String),
+///     Variant2(u32),
+/// }
+///
+/// let set = VariantSet::from([MyEnum::Variant2(42)]);
+/// let mut iter: Iter<'_, MyEnum> = set.iter();
+///
+/// assert_eq!(iter.size_hint(), (1, Some(1)));
+/// assert_eq!(format!("{:?}", iter), "[Variant2(42)]");
+///
+/// iter.next();
+/// assert_eq!(iter.len(), 0);
+/// ```
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Iter<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    S: VariantStorage<T> + 'a,
+{
+    inner: S::Iter<'a>,
+}
+
+impl<'a, T, S> Iter<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: S::Iter<'a>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, T, S> Iterator for Iter<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for Iter<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, S> ExactSizeIterator for Iter<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for Iter<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> fmt::Debug for Iter<'_, T, S>
+where
+    T: VariantEnum + fmt::Debug,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<T, S> Clone for Iter<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// A mutable iterator over the values of a `VariantSet`.
+///
+/// This `struct` is created by `VariantSet::iter_mut`, or by iterating over a `&mut VariantSet`.
+///
+/// Changing a value to a different variant through this iterator is a logic error: the value stays stored under its
+/// original variant, so the set will behave as if it still had that variant.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IterMut<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    S: VariantStorage<T> + 'a,
+{
+    inner: S::IterMut<'a>,
+}
+
+impl<'a, T, S> IterMut<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: S::IterMut<'a>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, T, S> Iterator for IterMut<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IterMut<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, S> ExactSizeIterator for IterMut<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for IterMut<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+}
+
+impl<'a, T, S> fmt::Debug for IterMut<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+    S::IterMut<'a>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+/// An owning iterator over the values of a `VariantSet`.
+///
+/// This `struct` is created by the `into_iter` method on `VariantSet`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IntoIter<T, S = DefaultStorage<T>>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    inner: S::IntoIter,
+}
+
+impl<T, S> IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: S::IntoIter) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T, S> Iterator for IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, S> ExactSizeIterator for IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> fmt::Debug for IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+    S::IntoIter: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+impl<T, S> Clone for IntoIter<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+    S::IntoIter: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// An iterator over the variants present in a `VariantSet`.
+///
+/// This `struct` is created by `VariantSet::variants`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Variants<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    S: VariantStorage<T> + 'a,
+{
+    inner: S::Iter<'a>,
+}
+
+impl<'a, T, S> Variants<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: S::Iter<'a>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T, S> Iterator for Variants<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = T::Variant;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(VariantEnum::variant)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for Variants<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(VariantEnum::variant)
+    }
+}
+
+impl<T, S> ExactSizeIterator for Variants<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for Variants<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> fmt::Debug for Variants<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: fmt::Debug,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<T, S> Clone for Variants<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// An iterator over the variants and values of a `VariantSet`.
+///
+/// This `struct` is created by `VariantSet::iter_pairs`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IterPairs<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    S: VariantStorage<T> + 'a,
+{
+    inner: S::Iter<'a>,
+}
+
+impl<'a, T, S> IterPairs<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: S::Iter<'a>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, T, S> Iterator for IterPairs<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = (T::Variant, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|value| (value.variant(), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IterPairs<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|value| (value.variant(), value))
+    }
+}
+
+impl<T, S> ExactSizeIterator for IterPairs<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for IterPairs<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> fmt::Debug for IterPairs<'_, T, S>
+where
+    T: VariantEnum + fmt::Debug,
+    T::Variant: fmt::Debug,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<T, S> Clone for IterPairs<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// An iterator over the values of a `VariantSet` whose variant is of a given kind.
+///
+/// This `struct` is created by `VariantSet::iter_kind`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IterKind<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    T::Variant: VariantKind,
+    S: VariantStorage<T> + 'a,
+{
+    inner: IterPairs<'a, T, S>,
+    kind: <T::Variant as VariantKind>::Kind,
+}
+
+impl<'a, T, S> IterKind<'a, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantKind,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: IterPairs<'a, T, S>, kind: <T::Variant as VariantKind>::Kind) -> Self {
+        Self { inner, kind }
+    }
+}
+
+impl<'a, T, S> Iterator for IterKind<'a, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantKind,
+    S: VariantStorage<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let kind = self.kind;
+        self.inner
+            .find(|(variant, _)| variant.kind() == kind)
+            .map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.inner.size_hint().1)
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IterKind<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantKind,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let kind = self.kind;
+        self.inner
+            .rfind(|(variant, _)| variant.kind() == kind)
+            .map(|(_, value)| value)
+    }
+}
+
+impl<T, S> FusedIterator for IterKind<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantKind,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> Clone for IterKind<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantKind,
+    S: VariantStorage<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            kind: self.kind,
+        }
+    }
+}
+
+impl<T, S> fmt::Debug for IterKind<'_, T, S>
+where
+    T: VariantEnum + fmt::Debug,
+    T::Variant: VariantKind,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the values of a `VariantSet` and the descriptions of their variants.
+///
+/// This `struct` is created by `VariantSet::iter_with_info`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct IterWithInfo<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T> + 'a,
+{
+    inner: IterPairs<'a, T, S>,
+}
+
+impl<'a, T, S> IterWithInfo<'a, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: IterPairs<'a, T, S>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, T, S> Iterator for IterWithInfo<'a, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+    type Item = (&'static VariantInfo, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(variant, value)| (variant.info(), value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for IterWithInfo<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(variant, value)| (variant.info(), value))
+    }
+}
+
+impl<T, S> ExactSizeIterator for IterWithInfo<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for IterWithInfo<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> Clone for IterWithInfo<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T, S> fmt::Debug for IterWithInfo<'_, T, S>
+where
+    T: VariantEnum + fmt::Debug,
+    T::Variant: VariantReflect,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// An iterator over the variants that are not in a `VariantSet`.
+///
+/// This `struct` is created by `VariantSet::missing`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Missing<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+    S: VariantStorage<T>,
+{
+    all: slice::Iter<'static, T::Variant>,
+    storage: &'a S,
+}
+
+impl<'a, T, S> Missing<'a, T, S>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(storage: &'a S) -> Self {
+        Self {
+            all: T::Variant::ALL.iter(),
+            storage,
+        }
+    }
+}
+
+impl<T, S> Iterator for Missing<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+    S: VariantStorage<T>,
+{
+    type Item = T::Variant;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let storage = self.storage;
+        self.all
+            .find(|variant| storage.get(**variant).is_none())
+            .copied()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.all.size_hint().1)
+    }
+}
+
+impl<T, S> DoubleEndedIterator for Missing<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let storage = self.storage;
+        self.all
+            .rfind(|variant| storage.get(**variant).is_none())
+            .copied()
+    }
+}
+
+impl<T, S> FusedIterator for Missing<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> Clone for Missing<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+    S: VariantStorage<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            all: self.all.clone(),
+            storage: self.storage,
+        }
+    }
+}
+
+impl<T, S> fmt::Debug for Missing<'_, T, S>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey + fmt::Debug,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A draining iterator over the values of a `VariantSet`.
+///
+/// This `struct` is created by `VariantSet::drain`. The set is empty once the iterator is dropped, even if it was not
+/// fully consumed.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Drain<'a, T, S = DefaultStorage<T>>
+where
+    T: VariantEnum + 'a,
+    S: VariantStorage<T> + 'a,
+{
+    inner: S::Drain<'a>,
+}
+
+impl<'a, T, S> Drain<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(inner: S::Drain<'a>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T, S> Iterator for Drain<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T, S> DoubleEndedIterator for Drain<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<T, S> ExactSizeIterator for Drain<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T, S> FusedIterator for Drain<'_, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+}
+
+impl<'a, T, S> fmt::Debug for Drain<'a, T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+    S::Drain<'a>: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+
+/// An iterator which removes and yields the values of a `VariantSet` that match a predicate.
+///
+/// This `struct` is created by `VariantSet::extract_if`. Values which have not been visited when the iterator is
+/// dropped are kept in the set.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ExtractIf<'a, T, F, S = DefaultStorage<T>>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    storage: &'a mut S,
+    /// The variants present when the iterator was created, which have not been visited yet.
+    variants: vec::IntoIter<T::Variant>,
+    pred: F,
+}
+
+impl<'a, T, F, S> ExtractIf<'a, T, F, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    pub(crate) fn new(storage: &'a mut S, pred: F) -> Self {
+        let variants = storage
+            .iter()
+            .map(VariantEnum::variant)
+            .collect::<Vec<_>>()
+            .into_iter();
+        Self {
+            storage,
+            variants,
+            pred,
+        }
+    }
+}
+
+impl<T, F, S> Iterator for ExtractIf<'_, T, F, S>
+where
+    T: VariantEnum,
+    F: FnMut(&mut T) -> bool,
+    S: VariantStorage<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for variant in self.variants.by_ref() {
+            if self.storage.get_mut(variant).is_some_and(&mut self.pred) {
+                return self.storage.remove(variant);
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.variants.len()))
+    }
+}
+
+impl<T, F, S> FusedIterator for ExtractIf<'_, T, F, S>
+where
+    T: VariantEnum,
+    F: FnMut(&mut T) -> bool,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, F, S> fmt::Debug for ExtractIf<'_, T, F, S>
+where
+    T: VariantEnum + fmt::Debug,
+    S: VariantStorage<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let storage = &*self.storage;
+        f.debug_list()
+            .entries(
+                self.variants
+                    .as_slice()
+                    .iter()
+                    .filter_map(|variant| storage.get(*variant)),
+            )
+            .finish()
+    }
+}
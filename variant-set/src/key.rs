@@ -0,0 +1,265 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{BuildHasher, BuildHasherDefault, Hash},
+};
+
+use nohash_hasher::NoHashHasher;
+
+/// Hashes keys that hash as a single integer by using that integer as the hash.
+///
+/// This is the fastest way to store a key, and is used for the variant enums generated by `#[derive(VariantEnum)]`
+/// as well as for integers, `bool` and `char`. It must only be used for keys whose `Hash` implementation writes exactly
+/// one integer, anything else panics in debug builds and collides in release builds.
+pub type DenseIndex = BuildHasherDefault<NoHashHasher<usize>>;
+
+/// Hashes keys with the standard library's `SipHash` implementation.
+///
+/// Works with any key, and is used for tuples and other keys made of several values.
+pub type SipHash = BuildHasherDefault<DefaultHasher>;
+
+/// A trait for the variant keys of a `VariantEnum`, choosing how the key is hashed inside a `VariantSet`.
+///
+/// The variant enums generated by `#[derive(VariantEnum)]` implement this trait. It only needs to be implemented by
+/// hand when implementing `VariantEnum` by hand with a custom key type. Use `DenseIndex` if the key hashes as a single
+/// integer, and `SipHash` otherwise.
+///
+/// # Examples
+/// ```
+/// use variant_set::{SipHash, VariantEnum, VariantKey, VariantSet};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Shape {
+///     Square { size: u32 },
+///     Rect { width: u32, height: u32 },
+/// }
+///
+/// // A key made of several fields must not use `DenseIndex`.
+/// #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// struct ShapeKey {
+///     sides: u8,
+///     regular: bool,
+/// }
+///
+/// impl VariantKey for ShapeKey {
+///     type BuildHasher = SipHash;
+/// }
+///
+/// impl VariantEnum for Shape {
+///     type Variant = ShapeKey;
+///
+///     fn variant(&self) -> ShapeKey {
+///         match self {
+///             Shape::Square { .. } => ShapeKey { sides: 4, regular: true },
+///             Shape::Rect { .. } => ShapeKey { sides: 4, regular: false },
+///         }
+///     }
+/// }
+///
+/// let mut set = VariantSet::new();
+/// set.set(Shape::Square { size: 1 });
+/// set.set(Shape::Rect { width: 1, height: 2 });
+/// set.set(Shape::Square { size: 2 });
+///
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set.get(ShapeKey { sides: 4, regular: true }), Some(&Shape::Square { size: 2 }));
+/// ```
+///
+/// Tuples and `char` can be used as keys directly:
+/// ```
+/// use variant_set::{VariantEnum, VariantSet};
+///
+/// struct Reading {
+///     sensor: (u8, u16),
+///     value: f64,
+/// }
+///
+/// impl VariantEnum for Reading {
+///     type Variant = (u8, u16);
+///
+///     fn variant(&self) -> (u8, u16) {
+///         self.sensor
+///     }
+/// }
+///
+/// struct Key(char, u32);
+///
+/// impl VariantEnum for Key {
+///     type Variant = char;
+///
+///     fn variant(&self) -> char {
+///         self.0
+///     }
+/// }
+///
+/// let mut readings = VariantSet::new();
+/// readings.set(Reading { sensor: (1, 300), value: 1.0 });
+/// readings.set(Reading { sensor: (1, 301), value: 2.0 });
+/// readings.set(Reading { sensor: (1, 300), value: 3.0 });
+/// assert_eq!(readings.len(), 2);
+/// assert_eq!(readings.get((1, 300)).map(|reading| reading.value), Some(3.0));
+///
+/// let keys: VariantSet<Key> = ('a'..='z').map(|c| Key(c, c as u32)).collect();
+/// assert_eq!(keys.len(), 26);
+/// assert_eq!(keys.get('q').map(|key| key.1), Some('q' as u32));
+/// ```
+pub trait VariantKey: Copy + Eq + Hash {
+    /// The hasher used to store this key. Either `DenseIndex`, `SipHash`, or any other `BuildHasher`.
+    type BuildHasher: BuildHasher + Default + Clone;
+}
+
+macro_rules! impl_variant_key {
+    ($hasher:ty => $($ty:ty),* $(,)?) => {
+        $(
+            impl VariantKey for $ty {
+                type BuildHasher = $hasher;
+            }
+        )*
+    };
+}
+
+impl_variant_key!(DenseIndex => u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, bool, char);
+impl_variant_key!(SipHash => u128, i128, (), &'static str);
+
+macro_rules! impl_variant_key_tuple {
+    ($($name:ident),+) => {
+        impl<$($name),+> VariantKey for ($($name,)+)
+        where
+            $($name: Copy + Eq + Hash,)+
+        {
+            type BuildHasher = SipHash;
+        }
+    };
+}
+
+impl_variant_key_tuple!(A);
+impl_variant_key_tuple!(A, B);
+impl_variant_key_tuple!(A, B, C);
+impl_variant_key_tuple!(A, B, C, D);
+impl_variant_key_tuple!(A, B, C, D, E);
+impl_variant_key_tuple!(A, B, C, D, E, F);
+impl_variant_key_tuple!(A, B, C, D, E, F, G);
+impl_variant_key_tuple!(A, B, C, D, E, F, G, H);
+
+/// A trait for variant keys that carry data, grouping keys into kinds without their data.
+///
+/// `#[derive(VariantEnum)]` implements this trait for every variant enum it generates. When some fields are marked
+/// with `#[variant(key)]`, the variant enum carries those fields and a separate fieldless `{Name}Kind` enum is
+/// generated as the kind. Otherwise the variant enum is its own kind.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantKind, VariantSet};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// # #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// enum KeyCode {
+///     A,
+///     B,
+/// }
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Input {
+///     Key {
+///         #[variant(key)]
+///         code: KeyCode,
+///         pressed: bool,
+///     },
+///     MouseMove(i32, i32),
+/// }
+///
+/// let mut set = VariantSet::new();
+/// set.set(Input::Key { code: KeyCode::A, pressed: true });
+/// set.set(Input::Key { code: KeyCode::B, pressed: true });
+/// set.set(Input::Key { code: KeyCode::A, pressed: false });
+/// set.set(Input::MouseMove(1, 2));
+///
+/// assert_eq!(set.len(), 3);
+/// assert_eq!(
+///     set.get(InputVariant::Key(KeyCode::A)),
+///     Some(&Input::Key { code: KeyCode::A, pressed: false }),
+/// );
+///
+/// assert_eq!(InputVariant::Key(KeyCode::B).kind(), InputKind::Key);
+/// assert_eq!(set.iter_kind(InputKind::Key).count(), 2);
+/// ```
+///
+/// When a variant has several key fields, its variant carries all of them, in declaration order:
+/// ```
+/// use variant_set::{VariantEnum, VariantSet};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Metric {
+///     Gauge(#[variant(key)] &'static str, f64),
+///     Counter {
+///         #[variant(key)]
+///         name: &'static str,
+///         #[variant(key)]
+///         shard: u8,
+///         count: u64,
+///     },
+/// }
+///
+/// let mut set = VariantSet::new();
+/// set.set(Metric::Gauge("cpu", 0.5));
+/// set.set(Metric::Counter { name: "requests", shard: 0, count: 10 });
+/// set.set(Metric::Counter { name: "requests", shard: 1, count: 4 });
+/// set.set(Metric::Gauge("cpu", 0.75));
+///
+/// assert_eq!(set.len(), 3);
+/// assert_eq!(set.get(MetricVariant::Gauge("cpu")), Some(&Metric::Gauge("cpu", 0.75)));
+/// assert!(set.contains(MetricVariant::Counter("requests", 1)));
+/// ```
+pub trait VariantKind: VariantKey {
+    /// The kind of the key, without its data.
+    type Kind: Copy + Eq;
+
+    /// Returns the kind of this key.
+    fn kind(self) -> Self::Kind;
+}
+
+/// A trait for variant keys without data, whose values can all be listed.
+///
+/// `#[derive(VariantEnum)]` and `impl_variant_enum!` implement this trait for variant enums without key fields, and
+/// for the `{Name}Kind` enums generated next to variant enums with key fields. It lets code that is generic over a
+/// `VariantEnum` count and name the variants, or find the ones missing from a set with `VariantSet::missing`.
+///
+/// # Examples
+/// ```
+/// use variant_set::{FiniteKey, VariantEnum, VariantSet};
+///
+/// #[derive(VariantEnum)]
+/// enum Channel {
+///     Red(u8),
+///     Green(u8),
+///     Blue(u8),
+/// }
+///
+/// /// Describes which variants of any set are missing.
+/// fn report<T>(set: &VariantSet<T>) -> String
+/// where
+///     T: VariantEnum,
+///     T::Variant: FiniteKey,
+/// {
+///     let missing: Vec<_> = set.missing().map(FiniteKey::name).collect();
+///     format!("{}/{} present, missing: {}", set.len(), T::Variant::COUNT, missing.join(", "))
+/// }
+///
+/// let set = VariantSet::from([Channel::Green(128)]);
+/// assert_eq!(report(&set), "1/3 present, missing: Red, Blue");
+///
+/// assert_eq!(ChannelVariant::ALL, [ChannelVariant::Red, ChannelVariant::Green, ChannelVariant::Blue]);
+/// assert_eq!(ChannelVariant::Blue.index(), 2);
+/// ```
+pub trait FiniteKey: VariantKey + 'static {
+    /// The number of values of the key.
+    const COUNT: usize;
+
+    /// All values of the key, in declaration order.
+    const ALL: &'static [Self];
+
+    /// Returns the position of this value in `ALL`.
+    fn index(self) -> usize;
+
+    /// Returns the name of this value.
+    fn name(self) -> &'static str;
+}
@@ -1,24 +1,148 @@
 #![warn(clippy::all, clippy::pedantic)]
 use std::{
-    collections::HashMap,
-    hash::{BuildHasherDefault, Hash},
+    borrow::Cow,
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
 };
 
-use nohash_hasher::NoHashHasher;
 pub use variant_set_derive::VariantEnum;
 
+mod constraints;
+mod diff;
+mod encoding;
+mod error;
+mod handlers;
+mod iter;
+mod key;
+mod macros;
+mod payload;
+mod reflect;
+mod sparse;
+mod std_enums;
+mod storage;
+mod tracked;
+mod transitions;
+mod visit;
+
+pub use constraints::{ValidatedVariantSet, VariantConstraints};
+pub use diff::VariantSetDiff;
+pub use encoding::{EncodeVariant, StableId};
+pub use error::{ConstraintViolation, DecodeError, InvalidTransition};
+pub use handlers::VariantHandlers;
+pub use iter::{
+    Drain, ExtractIf, IntoIter, Iter, IterKind, IterMut, IterPairs, IterWithInfo, Missing, Variants,
+};
+pub use key::{DenseIndex, FiniteKey, SipHash, VariantKey, VariantKind};
+pub use payload::{VariantMarker, VariantOf};
+pub use reflect::{FieldInfo, VariantInfo, VariantReflect};
+pub use sparse::{SparseIntoIter, SparseIter, SparseIterMut, SparseStorage, SparseVariantSet};
+pub use std_enums::{BoundVariant, IpAddrVariant, OptionVariant, ResultVariant, SocketAddrVariant};
+pub use storage::{DefaultStorage, HashDrain, HashStorage, VariantStorage};
+pub use tracked::{TrackedVariantSet, VariantChanges};
+pub use transitions::{DeclaredTransitions, VariantTransitions};
+pub use visit::{VariantVisit, VariantVisitMut};
+
+/// The hasher used for the variants of a `VariantSet<T>`, as chosen by the variant key.
+pub(crate) type VariantHasher<T> = <<T as VariantEnum>::Variant as VariantKey>::BuildHasher;
+
 /// A trait that must be implemented by enums that are used with `VariantSet`.
 ///
 /// This trait provides a way to get the variant of an enum, which is another enum that represents the variants of the original enum,
 /// but without the data.
+///
+/// The trait is also implemented for `&T`, `&mut T`, `Box<T>`, `Rc<T>`, `Arc<T>` and `Cow<'_, T>` when `T` implements it,
+/// using the variant of the pointed-to value. This allows sets of shared or borrowed values without cloning them.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use variant_set::{VariantSet, VariantEnum};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Event {
+///     Loaded(Vec<u8>),
+///     Closed,
+/// }
+///
+/// let event = Arc::new(Event::Loaded(vec![0; 1024]));
+///
+/// let mut set: VariantSet<Arc<Event>> = VariantSet::new();
+/// set.set(Arc::clone(&event));
+///
+/// assert!(set.contains(EventVariant::Loaded));
+/// assert_eq!(Arc::strong_count(&event), 2);
+/// ```
+///
+/// Variants marked with `#[variant(slot = "...")]` share the variant of the variant enum with that name, so that a set
+/// holds at most one of them. A slot cannot have the same name as a variant that is not part of it, and variants in a
+/// slot cannot have key fields:
+/// ```
+/// use variant_set::{VariantEnum, VariantSet};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Status {
+///     #[variant(slot = "Connection")]
+///     Connected { peer: String },
+///     #[variant(slot = "Connection")]
+///     Connecting,
+///     Battery(u8),
+/// }
+///
+/// let mut set = VariantSet::new();
+/// set.set(Status::Connecting);
+/// set.set(Status::Battery(80));
+///
+/// let previous = set.set(Status::Connected { peer: "server".to_string() });
+/// assert_eq!(previous, Some(Status::Connecting));
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(Status::Connecting.variant(), StatusVariant::Connection);
+/// ```
 pub trait VariantEnum {
     /// The enum that represents the variants of the original enum, but without the data.
-    type Variant: Copy + Eq + Hash;
+    ///
+    /// When implementing this trait by hand, any `VariantKey` can be used here, such as an integer, a `char`, a tuple,
+    /// or your own key type.
+    type Variant: VariantKey;
 
     /// For a given value of the enum, returns the variant of the enum.
     fn variant(&self) -> Self::Variant;
 }
 
+/// Implements `VariantEnum` for a pointer-like type by forwarding to the pointed-to value.
+macro_rules! forward_variant_enum {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<T> VariantEnum for $ty
+            where
+                T: VariantEnum + ?Sized,
+            {
+                type Variant = T::Variant;
+
+                fn variant(&self) -> Self::Variant {
+                    (**self).variant()
+                }
+            }
+        )*
+    };
+}
+
+forward_variant_enum!(&T, &mut T, Box<T>, Rc<T>, Arc<T>);
+
+impl<T> VariantEnum for Cow<'_, T>
+where
+    T: VariantEnum + ToOwned + ?Sized,
+{
+    type Variant = T::Variant;
+
+    fn variant(&self) -> Self::Variant {
+        (**self).variant()
+    }
+}
+
 /// A set of values that are variants of an enum. The set can contain at most one value for each variant.
 /// Functionally equivalent to a `HashSet<T>`, but the enum variants can contain complex data.
 ///
@@ -52,13 +176,71 @@ pub trait VariantEnum {
 ///
 /// # Performance
 ///
-/// The `VariantSet` is backed by a `HashMap` and provides constant time insertion, removal, and lookup.
+/// By default, the `VariantSet` is backed by an `IndexMap` and provides constant time insertion, removal, and lookup.
+///
+/// # Storage
+///
+/// The values are kept in a storage implementing `VariantStorage`, given as the second type parameter. It defaults to
+/// `HashStorage`, and `SparseStorage` is provided for enums with many variants, see `SparseVariantSet`. `new`,
+/// `with_capacity` and `From<[T; N]>` are only available with the default storage, use `Default`, `from_storage` or
+/// `FromIterator` with other storages. Storages that do not implement `Default` can only be used with `from_storage`.
+///
+/// # Generated sets
+///
+/// `#[variant_enum(set = "...")]` on a `#[derive(VariantEnum)]` enum generates a struct with the methods of a
+/// `VariantSet`, storing the data of each variant in its own `Option` field named after the variant. The data is
+/// borrowed with `get_<variant>` and `get_<variant>_mut`, and borrowing iterators yield `{Set}Ref` and `{Set}Mut`
+/// enums of references to the data. Use `set(name = "...", derive(...))` to derive traits on the struct, deriving
+/// `PartialEq` also generates `contains_exact` and `remove_exact`. Variants marked with `#[variant(boxed)]` are boxed,
+/// so that a large variant does not make the struct large:
+/// ```
+/// use variant_set::{VariantEnum, VariantSet};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// #[variant_enum(set(name = "ComponentSet", derive(Debug, PartialEq)))]
+/// enum Component {
+///     #[variant(boxed)]
+///     Texture([u8; 4096]),
+///     Position { x: f32, y: f32 },
+///     Visible,
+/// }
+///
+/// let mut set = ComponentSet::new();
+/// set.set(Component::Position { x: 1.0, y: 2.0 });
+/// set.set(Component::Visible);
+///
+/// assert_eq!(set.position, Some((1.0, 2.0)));
+/// assert!(std::mem::size_of::<ComponentSet>() < 64);
+/// assert!(set.contains_exact(&Component::Position { x: 1.0, y: 2.0 }));
+///
+/// set.get_or_insert(Component::Texture([0; 4096]));
+/// set.get_texture_mut().unwrap()[0] = 255;
+/// assert_eq!(set.get_texture().map(|texture| texture[0]), Some(255));
+///
+/// for data in &mut set {
+///     if let ComponentSetMut::Position((x, _)) = data {
+///         *x += 1.0;
+///     }
+/// }
+/// assert_eq!(set.get(ComponentVariant::Position), Some(ComponentSetRef::Position(&(2.0, 2.0))));
 ///
-pub struct VariantSet<T>
+/// let set: VariantSet<Component> = set.into();
+/// assert_eq!(set.get(ComponentVariant::Position), Some(&Component::Position { x: 2.0, y: 2.0 }));
+///
+/// let mut set = ComponentSet::from(set);
+/// let removed = set.extract_if(|value| *value == Component::Visible).collect::<Vec<_>>();
+/// assert_eq!(removed, [Component::Visible]);
+/// assert_eq!(set.missing().collect::<Vec<_>>(), [ComponentVariant::Visible]);
+/// assert_eq!(set.drain().count(), 2);
+/// assert!(set.is_empty());
+/// ```
+pub struct VariantSet<T, S = DefaultStorage<T>>
 where
     T: VariantEnum,
+    S: VariantStorage<T>,
 {
-    data: HashMap<T::Variant, T, BuildHasherDefault<NoHashHasher<usize>>>,
+    storage: S,
+    marker: PhantomData<T>,
 }
 
 impl<T> VariantSet<T>
@@ -81,9 +263,7 @@ where
     /// ```
     #[must_use]
     pub fn new() -> Self {
-        Self {
-            data: HashMap::with_hasher(BuildHasherDefault::default()),
-        }
+        Self::default()
     }
 
     /// Creates a new `VariantSet` with a specified capacity.
@@ -103,11 +283,66 @@ where
     /// ```
     #[must_use]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::from_storage(HashStorage::with_capacity(capacity))
+    }
+}
+
+impl<T, S> VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    /// Creates a new `VariantSet` keeping its values in the given storage.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{HashStorage, VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = VariantSet::from_storage(HashStorage::with_capacity(10));
+    /// set.set(MyEnum::Variant2(42));
+    /// assert!(set.capacity() >= 10);
+    /// ```
+    #[must_use]
+    pub fn from_storage(storage: S) -> Self {
         Self {
-            data: HashMap::with_capacity_and_hasher(capacity, BuildHasherDefault::default()),
+            storage,
+            marker: PhantomData,
         }
     }
 
+    /// Returns a reference to the storage of the set.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{SparseVariantSet, VariantEnum, VariantStorage};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let set: SparseVariantSet<MyEnum> = [MyEnum::Variant2(42)].into_iter().collect();
+    /// assert!(set.storage().is_dense());
+    /// assert_eq!(set.storage().len(), 1);
+    /// ```
+    #[must_use]
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Consumes the set and returns its storage.
+    #[must_use]
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
     /// Returns the number of elements this set can hold without reallocating.
     ///
     /// # Examples
@@ -125,7 +360,7 @@ where
     /// ```
     #[must_use]
     pub fn capacity(&self) -> usize {
-        self.data.capacity()
+        self.storage.capacity()
     }
 
     /// Clears the set, removing all values.
@@ -146,7 +381,7 @@ where
     /// assert!(set.is_empty());
     /// ```
     pub fn clear(&mut self) {
-        self.data.clear();
+        self.storage.clear();
     }
 
     /// Adds a value to the set.
@@ -173,11 +408,12 @@ where
     /// assert!(!set.insert(MyEnum::Variant1("World".to_string())));
     /// ```
     pub fn insert(&mut self, value: T) -> bool {
-        if let std::collections::hash_map::Entry::Vacant(entry) = self.data.entry(value.variant()) {
-            entry.insert(value);
-            true
-        } else {
+        let variant = value.variant();
+        if self.storage.get(variant).is_some() {
             false
+        } else {
+            self.storage.insert(variant, value);
+            true
         }
     }
 
@@ -201,7 +437,7 @@ where
     /// assert_eq!(previous, Some(MyEnum::Variant1("Hello".to_string())));
     /// ```
     pub fn set(&mut self, value: T) -> Option<T> {
-        self.data.insert(value.variant(), value)
+        self.storage.insert(value.variant(), value)
     }
 
     /// Returns `true` if the set contains a value.
@@ -221,7 +457,7 @@ where
     /// assert!(set.contains(MyEnumVariant::Variant1));
     /// ```
     pub fn contains(&self, value: T::Variant) -> bool {
-        self.data.contains_key(&value)
+        self.storage.get(value).is_some()
     }
 
     /// Returns `true` if the set contains a value that is equal to the given value.
@@ -245,7 +481,7 @@ where
     where
         T: PartialEq,
     {
-        matches!(self.data.get(&value.variant()), Some(v) if v == value)
+        matches!(self.storage.get(value.variant()), Some(v) if v == value)
     }
 
     /// Clears the set, returning all elements as an iterator. Keeps the allocated memory for reuse.
@@ -269,8 +505,44 @@ where
     /// assert!(values.contains(&MyEnum::Variant1("Hello".to_string())));
     /// assert!(values.contains(&MyEnum::Variant2(42)));
     /// ```
-    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
-        self.data.drain().map(|(_, value)| value)
+    pub fn drain(&mut self) -> Drain<'_, T, S> {
+        Drain::new(self.storage.drain())
+    }
+
+    /// Creates an iterator which removes and yields every value for which `pred` returns `true`.
+    /// Values for which `pred` returns `false` are kept in the set.
+    ///
+    /// If the iterator is dropped before it is fully consumed, the values which have not been visited yet are kept.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(u32),
+    ///     Variant2(u32),
+    ///     Variant3(u32),
+    /// }
+    ///
+    /// let mut set = VariantSet::from([MyEnum::Variant1(1), MyEnum::Variant2(2), MyEnum::Variant3(3)]);
+    ///
+    /// let mut odd: Vec<_> = set
+    ///     .extract_if(|value| match value {
+    ///         MyEnum::Variant1(n) | MyEnum::Variant2(n) | MyEnum::Variant3(n) => *n % 2 == 1,
+    ///     })
+    ///     .collect();
+    /// odd.sort_by_key(|value| value.variant());
+    ///
+    /// assert_eq!(odd, vec![MyEnum::Variant1(1), MyEnum::Variant3(3)]);
+    /// assert_eq!(set.len(), 1);
+    /// assert!(set.contains(MyEnumVariant::Variant2));
+    /// ```
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, S>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf::new(&mut self.storage, pred)
     }
 
     /// Returns a reference to the value in the set, if any, that is equal to the given value.
@@ -291,7 +563,7 @@ where
     /// assert_eq!(value, Some(&MyEnum::Variant1("Hello".to_string())));
     /// ```
     pub fn get(&self, value: T::Variant) -> Option<&T> {
-        self.data.get(&value)
+        self.storage.get(value)
     }
 
     /// Inserts the given `value` into the set if it is not present, then returns a reference to the value in the set.
@@ -311,7 +583,8 @@ where
     /// assert_eq!(value, &MyEnum::Variant1("Hello".to_string()));
     /// ```
     pub fn get_or_insert(&mut self, default: T) -> &T {
-        self.data.entry(default.variant()).or_insert(default)
+        self.storage
+            .get_or_insert_with(default.variant(), || default)
     }
 
     /// Returns `true` if the set contains no elements.
@@ -331,7 +604,7 @@ where
     /// ```
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.storage.is_empty()
     }
 
     /// An iterator visiting all elements in arbitrary order. The iterator element type is `&'a T`.
@@ -354,8 +627,223 @@ where
     ///    println!("{:?}", value);
     /// }
     /// ```
-    pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.data.values()
+    pub fn iter(&self) -> Iter<'_, T, S> {
+        Iter::new(self.storage.iter())
+    }
+
+    /// An iterator visiting all elements whose variant is of the given kind, in arbitrary order. The iterator element
+    /// type is `&'a T`.
+    ///
+    /// This is useful when the variant carries key fields marked with `#[variant(key)]`, so that there can be several
+    /// values of the same kind in the set. See `VariantKind`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Setting {
+    ///     Env {
+    ///         #[variant(key)]
+    ///         name: &'static str,
+    ///         value: String,
+    ///     },
+    ///     Verbose(bool),
+    /// }
+    ///
+    /// let set = VariantSet::from([
+    ///     Setting::Env { name: "HOME", value: "/root".to_string() },
+    ///     Setting::Env { name: "PATH", value: "/bin".to_string() },
+    ///     Setting::Verbose(true),
+    /// ]);
+    ///
+    /// let mut names: Vec<_> = set
+    ///     .iter_kind(SettingKind::Env)
+    ///     .map(|setting| match setting {
+    ///         Setting::Env { name, .. } => *name,
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect();
+    /// names.sort();
+    ///
+    /// assert_eq!(names, vec!["HOME", "PATH"]);
+    /// ```
+    pub fn iter_kind(&self, kind: <T::Variant as VariantKind>::Kind) -> IterKind<'_, T, S>
+    where
+        T::Variant: VariantKind,
+    {
+        IterKind::new(self.iter_pairs(), kind)
+    }
+
+    /// Creates a new set containing references to the values of this set, without cloning them.
+    ///
+    /// `VariantEnum` is implemented for references, `Box`, `Rc`, `Arc` and `Cow` of a `VariantEnum`, so sets of these
+    /// are keyed by the same variants as the values they point to.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let set = VariantSet::from([MyEnum::Variant1("Hello".to_string()), MyEnum::Variant2(42)]);
+    ///
+    /// let mut refs: VariantSet<&MyEnum> = set.as_refs();
+    /// refs.remove(MyEnumVariant::Variant1);
+    ///
+    /// assert_eq!(refs.get(MyEnumVariant::Variant2), Some(&&MyEnum::Variant2(42)));
+    /// assert_eq!(refs.len(), 1);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn as_refs(&self) -> VariantSet<&T> {
+        let mut refs = VariantSet::with_capacity(self.len());
+        refs.extend(self.iter());
+        refs
+    }
+
+    /// An iterator visiting all elements in arbitrary order, with mutable references to the values.
+    /// The iterator element type is `&'a mut T`.
+    ///
+    /// Changing a value to a different variant is a logic error: the value stays stored under its original variant.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = VariantSet::new();
+    /// set.set(MyEnum::Variant1("Hello".to_string()));
+    /// set.set(MyEnum::Variant2(42));
+    ///
+    /// for value in set.iter_mut() {
+    ///     if let MyEnum::Variant2(number) = value {
+    ///         *number += 1;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(set.get(MyEnumVariant::Variant2), Some(&MyEnum::Variant2(43)));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, S> {
+        IterMut::new(self.storage.iter_mut())
+    }
+
+    /// An iterator visiting all variants present in the set in arbitrary order. The iterator element type is `T::Variant`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    ///     Variant3(bool),
+    /// }
+    ///
+    /// let mut set = VariantSet::new();
+    /// set.set(MyEnum::Variant1("Hello".to_string()));
+    /// set.set(MyEnum::Variant3(true));
+    ///
+    /// let mut variants: Vec<_> = set.variants().collect();
+    /// variants.sort();
+    /// assert_eq!(variants, vec![MyEnumVariant::Variant1, MyEnumVariant::Variant3]);
+    /// ```
+    pub fn variants(&self) -> Variants<'_, T, S> {
+        Variants::new(self.storage.iter())
+    }
+
+    /// An iterator visiting all variants and their values in arbitrary order. The iterator element type is
+    /// `(T::Variant, &'a T)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = VariantSet::new();
+    /// set.set(MyEnum::Variant1("Hello".to_string()));
+    /// set.set(MyEnum::Variant2(42));
+    ///
+    /// for (variant, value) in set.iter_pairs() {
+    ///     println!("{:?}: {:?}", variant, value);
+    /// }
+    /// ```
+    pub fn iter_pairs(&self) -> IterPairs<'_, T, S> {
+        IterPairs::new(self.storage.iter())
+    }
+
+    /// An iterator visiting all values and the descriptions of their variants in arbitrary order. The iterator element
+    /// type is `(&'static VariantInfo, &'a T)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug)]
+    /// enum Property {
+    ///     /// The displayed name.
+    ///     Name(String),
+    ///     Size { width: u32, height: u32 },
+    /// }
+    ///
+    /// let set = VariantSet::from([
+    ///     Property::Name("Window".to_string()),
+    ///     Property::Size { width: 640, height: 480 },
+    /// ]);
+    ///
+    /// let mut fields: Vec<String> = set
+    ///     .iter_with_info()
+    ///     .flat_map(|(info, _)| info.fields.iter().map(move |field| format!("{}.{}", info.name, field.name)))
+    ///     .collect();
+    /// fields.sort();
+    ///
+    /// assert_eq!(fields, ["Name.0", "Size.height", "Size.width"]);
+    /// ```
+    pub fn iter_with_info(&self) -> IterWithInfo<'_, T, S>
+    where
+        T::Variant: VariantReflect,
+    {
+        IterWithInfo::new(self.iter_pairs())
+    }
+
+    /// An iterator visiting the variants that are not in the set, in declaration order.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Header {
+    ///     Host(String),
+    ///     Accept(String),
+    ///     UserAgent(String),
+    /// }
+    ///
+    /// let set = VariantSet::from([Header::Accept("*/*".to_string())]);
+    /// let missing: Vec<_> = set.missing().collect();
+    ///
+    /// assert_eq!(missing, [HeaderVariant::Host, HeaderVariant::UserAgent]);
+    /// ```
+    pub fn missing(&self) -> Missing<'_, T, S>
+    where
+        T::Variant: FiniteKey,
+    {
+        Missing::new(&self.storage)
     }
 
     /// Returns the number of elements in the set.
@@ -378,7 +866,7 @@ where
     /// ```
     #[must_use]
     pub fn len(&self) -> usize {
-        self.data.len()
+        self.storage.len()
     }
 
     /// Removes a variant from the set. Returns the value if it existed.
@@ -399,7 +887,7 @@ where
     /// assert_eq!(value, Some(MyEnum::Variant1("Hello".to_string())));
     /// ```
     pub fn remove(&mut self, value: T::Variant) -> Option<T> {
-        self.data.remove(&value)
+        self.storage.remove(value)
     }
 
     /// Removes a variant from the set if it is equal to the given value. Returns the value if it existed.
@@ -427,8 +915,8 @@ where
     where
         T: PartialEq,
     {
-        match self.data.get(&value.variant()) {
-            Some(v) if v == value => self.data.remove(&value.variant()),
+        match self.storage.get(value.variant()) {
+            Some(v) if v == value => self.storage.remove(value.variant()),
             _ => None,
         }
     }
@@ -455,7 +943,7 @@ where
     /// assert!(set.capacity() >= 10);
     /// ```
     pub fn reserve(&mut self, additional: usize) {
-        self.data.reserve(additional);
+        self.storage.reserve(additional);
     }
 
     /// Tries to reserve capacity for at least `additional` more elements to be inserted in the set.
@@ -478,16 +966,19 @@ where
     /// let mut set: VariantSet<MyEnum> = VariantSet::new();
     /// set.try_reserve(10).unwrap();
     /// assert!(set.capacity() >= 10);
+    ///
+    /// assert!(set.try_reserve(usize::MAX).is_err());
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns a `std::collections::TryReserveError` if the new capacity would overflow usize.
+    /// Returns a `std::collections::TryReserveError` if the new capacity would overflow usize or the allocator reports
+    /// a failure.
     pub fn try_reserve(
         &mut self,
         additional: usize,
     ) -> Result<(), std::collections::TryReserveError> {
-        self.data.try_reserve(additional)
+        self.storage.try_reserve(additional)
     }
 
     /// Shrinks the capacity of the set with a lower limit. It will drop down to no lower than the supplied limit while maintaining the internal
@@ -511,7 +1002,7 @@ where
     /// assert!(set.capacity() >= 5);
     /// ```
     pub fn shrink_to(&mut self, min_capacity: usize) {
-        self.data.shrink_to(min_capacity);
+        self.storage.shrink_to(min_capacity);
     }
 
     /// Shrinks the capacity of the set as much as possible.
@@ -534,7 +1025,7 @@ where
     /// assert!(set.capacity() >= 1);
     /// ```
     pub fn shrink_to_fit(&mut self) {
-        self.data.shrink_to_fit();
+        self.storage.shrink_to(0);
     }
 
     /// Removes and returns the value in the set, if any, that is equal to the given value.
@@ -555,16 +1046,17 @@ where
     /// assert_eq!(value, Some(MyEnum::Variant1("Hello".to_string())));
     /// ```
     pub fn take(&mut self, value: T::Variant) -> Option<T> {
-        self.data.remove(&value)
+        self.storage.remove(value)
     }
 }
 
-impl<T> Default for VariantSet<T>
+impl<T, S> Default for VariantSet<T, S>
 where
     T: VariantEnum,
+    S: VariantStorage<T> + Default,
 {
-    /// Creates a new `VariantSet` with a default capacity.
-    /// The default capacity is the capacity of a newly created `HashMap`.
+    /// Creates a new `VariantSet` with a default storage.
+    /// With the default storage, the default capacity is the capacity of a newly created `IndexMap`.
     ///
     /// # Examples
     /// ```
@@ -579,13 +1071,14 @@ where
     /// let set: VariantSet<MyEnum> = Default::default();
     /// ```
     fn default() -> Self {
-        Self::new()
+        Self::from_storage(S::default())
     }
 }
 
-impl<T> Clone for VariantSet<T>
+impl<T, S> Clone for VariantSet<T, S>
 where
     T: VariantEnum + Clone,
+    S: VariantStorage<T> + Clone,
 {
     /// Clones the set. The values are cloned using their `Clone` implementation.
     ///
@@ -607,16 +1100,15 @@ where
     /// assert_eq!(set, cloned);
     /// ```
     fn clone(&self) -> Self {
-        Self {
-            data: self.data.clone(),
-        }
+        Self::from_storage(self.storage.clone())
     }
 }
 
-impl<T> std::fmt::Debug for VariantSet<T>
+impl<T, S> std::fmt::Debug for VariantSet<T, S>
 where
     T: VariantEnum + std::fmt::Debug,
     T::Variant: std::fmt::Debug,
+    S: VariantStorage<T>,
 {
     /// Formats the set as a map of variants to values.
     /// The values are formatted using their `Debug` implementation.
@@ -637,15 +1129,19 @@ where
     /// set.set(MyEnum::Variant2(42));
     ///
     /// println!("{:?}", set);
+    ///
+    /// let set = VariantSet::from([MyEnum::Variant2(42)]);
+    /// assert_eq!(format!("{:?}", set), "{Variant2: Variant2(42)}");
     /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_map().finish()
+        f.debug_map().entries(self.iter_pairs()).finish()
     }
 }
 
-impl<T> PartialEq for VariantSet<T>
+impl<T, S> PartialEq for VariantSet<T, S>
 where
     T: VariantEnum + PartialEq,
+    S: VariantStorage<T>,
 {
     /// Compares two sets for equality.
     /// Two sets are equal if they contain the same variants, regardless of the order.
@@ -672,15 +1168,183 @@ where
     /// assert_eq!(set1, set2);
     /// ```
     fn eq(&self, other: &Self) -> bool {
-        self.data == other.data
+        self.len() == other.len()
+            && self
+                .iter_pairs()
+                .all(|(variant, value)| other.get(variant) == Some(value))
+    }
+}
+
+impl<T, S> Eq for VariantSet<T, S>
+where
+    T: VariantEnum + Eq,
+    S: VariantStorage<T>,
+{
+}
+
+impl<T, S> Hash for VariantSet<T, S>
+where
+    T: VariantEnum + Hash,
+    S: VariantStorage<T>,
+{
+    /// Hashes the set. The hash does not depend on the order in which the values were inserted, so two equal sets
+    /// always have the same hash.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{Hash, Hasher};
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, Clone, PartialEq, Eq, Hash)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    ///     Variant3(bool),
+    ///     Variant4,
+    /// }
+    ///
+    /// fn hash(set: &VariantSet<MyEnum>) -> u64 {
+    ///     let mut hasher = DefaultHasher::new();
+    ///     set.hash(&mut hasher);
+    ///     hasher.finish()
+    /// }
+    ///
+    /// let values = [
+    ///     MyEnum::Variant1("Hello".to_string()),
+    ///     MyEnum::Variant2(42),
+    ///     MyEnum::Variant3(true),
+    ///     MyEnum::Variant4,
+    /// ];
+    ///
+    /// // Every insertion order of the same values produces an equal set with the same hash.
+    /// let expected = hash(&VariantSet::from(values.clone()));
+    /// for a in 0..4 {
+    ///     for b in 0..4 {
+    ///         for c in 0..4 {
+    ///             for d in 0..4 {
+    ///                 let order = [a, b, c, d];
+    ///                 if (0..4).all(|i| order.contains(&i)) {
+    ///                     let set: VariantSet<MyEnum> = order.iter().map(|&i| values[i].clone()).collect();
+    ///                     assert_eq!(set, VariantSet::from(values.clone()));
+    ///                     assert_eq!(hash(&set), expected);
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// // Replacing a value also changes the hash.
+    /// let mut changed = VariantSet::from(values.clone());
+    /// changed.set(MyEnum::Variant2(10));
+    /// assert_ne!(hash(&changed), expected);
+    /// ```
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Each value is hashed on its own with a fixed hasher and the results are combined with a commutative
+        // operation, so that the iteration order of the storage does not matter.
+        let combined = self
+            .iter()
+            .map(|value| {
+                let mut hasher = DefaultHasher::new();
+                value.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0u64, u64::wrapping_add);
+
+        state.write_usize(self.len());
+        state.write_u64(combined);
     }
 }
 
-impl<T> Eq for VariantSet<T> where T: VariantEnum + Eq {}
+impl<T, S> VariantSet<T, S>
+where
+    T: VariantEnum,
+    T::Variant: Ord,
+    S: VariantStorage<T>,
+{
+    /// Returns the entries of the set, sorted by variant.
+    fn sorted_entries(&self) -> Vec<(T::Variant, &T)> {
+        let mut entries: Vec<_> = self.iter_pairs().collect();
+        entries.sort_unstable_by_key(|(variant, _)| *variant);
+        entries
+    }
+}
 
-impl<T> Extend<T> for VariantSet<T>
+impl<T, S> PartialOrd for VariantSet<T, S>
+where
+    T: VariantEnum + PartialOrd,
+    T::Variant: Ord,
+    S: VariantStorage<T>,
+{
+    /// Compares two sets lexicographically, visiting the values of both sets in variant declaration order.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq, PartialOrd)]
+    /// enum MyEnum {
+    ///     Variant1(f64),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let a = VariantSet::from([MyEnum::Variant1(1.0)]);
+    /// let b = VariantSet::from([MyEnum::Variant1(2.0)]);
+    /// assert!(a < b);
+    /// ```
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.sorted_entries()
+            .into_iter()
+            .partial_cmp(other.sorted_entries())
+    }
+}
+
+impl<T, S> Ord for VariantSet<T, S>
+where
+    T: VariantEnum + Ord,
+    T::Variant: Ord,
+    S: VariantStorage<T>,
+{
+    /// Compares two sets lexicographically, visiting the values of both sets in variant declaration order.
+    ///
+    /// A set which contains an earlier declared variant is ordered before a set which does not, and a set is ordered
+    /// before any set it is a strict prefix of.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    /// enum MyEnum {
+    ///     Variant1(u32),
+    ///     Variant2(u32),
+    ///     Variant3(u32),
+    /// }
+    ///
+    /// let mut sets = vec![
+    ///     VariantSet::from([MyEnum::Variant3(1)]),
+    ///     VariantSet::from([MyEnum::Variant2(5), MyEnum::Variant1(2)]),
+    ///     VariantSet::from([MyEnum::Variant1(2)]),
+    ///     VariantSet::from([MyEnum::Variant1(1), MyEnum::Variant3(1)]),
+    /// ];
+    /// sets.sort();
+    ///
+    /// assert_eq!(sets, vec![
+    ///     VariantSet::from([MyEnum::Variant1(1), MyEnum::Variant3(1)]),
+    ///     VariantSet::from([MyEnum::Variant1(2)]),
+    ///     VariantSet::from([MyEnum::Variant1(2), MyEnum::Variant2(5)]),
+    ///     VariantSet::from([MyEnum::Variant3(1)]),
+    /// ]);
+    /// ```
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted_entries().cmp(&other.sorted_entries())
+    }
+}
+
+impl<T, S> Extend<T> for VariantSet<T, S>
 where
     T: VariantEnum,
+    S: VariantStorage<T>,
 {
     /// Extends the set with the contents of an iterator.
     /// If the set already contains a value that maps to the same variant, the value will be replaced.
@@ -711,12 +1375,13 @@ where
     }
 }
 
-impl<T> IntoIterator for VariantSet<T>
+impl<T, S> IntoIterator for VariantSet<T, S>
 where
     T: VariantEnum,
+    S: VariantStorage<T>,
 {
     type Item = T;
-    type IntoIter = std::collections::hash_map::IntoValues<T::Variant, T>;
+    type IntoIter = IntoIter<T, S>;
 
     /// Consumes the set and returns an iterator over the values.
     ///
@@ -741,13 +1406,80 @@ where
     /// assert!(values.contains(&MyEnum::Variant2(42)));
     /// ```
     fn into_iter(self) -> Self::IntoIter {
-        self.data.into_values()
+        IntoIter::new(self.storage.into_values())
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, S>;
+
+    /// Returns an iterator over references to the values. See `VariantSet::iter`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let set = VariantSet::from([MyEnum::Variant1("Hello".to_string()), MyEnum::Variant2(42)]);
+    ///
+    /// for value in &set {
+    ///     println!("{:?}", value);
+    /// }
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, S> IntoIterator for &'a mut VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, S>;
+
+    /// Returns an iterator over mutable references to the values. See `VariantSet::iter_mut`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = VariantSet::from([MyEnum::Variant1("Hello".to_string())]);
+    ///
+    /// for value in &mut set {
+    ///     if let MyEnum::Variant1(text) = value {
+    ///         text.push_str(", World");
+    ///     }
+    /// }
+    ///
+    /// assert!(set.contains_exact(&MyEnum::Variant1("Hello, World".to_string())));
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
 
-impl<T> FromIterator<T> for VariantSet<T>
+impl<T, S> FromIterator<T> for VariantSet<T, S>
 where
     T: VariantEnum,
+    S: VariantStorage<T> + Default,
 {
     /// Creates a new `VariantSet` from an iterator.
     /// If the iterator yields multiple values that map to the same variant, the last value will be used.
@@ -763,13 +1495,13 @@ where
     /// }
     ///
     /// let iter = vec![MyEnum::Variant1("Hello".to_string()), MyEnum::Variant2(42), MyEnum::Variant1("World".to_string())].into_iter();
-    /// let set = VariantSet::from_iter(iter);
+    /// let set: VariantSet<MyEnum> = VariantSet::from_iter(iter);
     ///
     /// assert_eq!(set.len(), 2);
     /// assert!(set.contains_exact(&MyEnum::Variant1("World".to_string())));
     /// ```
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut set = VariantSet::new();
+        let mut set = Self::default();
         set.extend(iter);
         set
     }
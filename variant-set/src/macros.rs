@@ -0,0 +1,199 @@
+/// Implements `VariantEnum` for an enum that cannot use `#[derive(VariantEnum)]`, such as an enum from another crate.
+///
+/// The macro generates the same variant enum as the derive macro, with one variant for each listed variant of the
+/// original enum, and implements `VariantEnum` using it. Every variant of the original enum must be listed, with `(..)`
+/// after tuple variants and `{ .. }` after struct variants. Attributes such as doc comments are copied onto the
+/// generated enum and its variants.
+///
+/// The variant enum implements `FiniteKey` and `VariantReflect`. Since the macro does not see the fields of the
+/// variants, and cannot read their doc comments, the `VariantInfo` of each variant only has its name and index.
+///
+/// Because of the orphan rule, `VariantEnum` cannot be implemented directly for an enum from another crate. Use the
+/// `struct` form to wrap it in a local newtype, which implements `VariantEnum` and converts from and into the wrapped
+/// enum:
+///
+/// ```
+/// mod remote {
+///     // Imagine this enum is defined in another crate.
+///     #[derive(Debug, PartialEq)]
+///     pub enum Event {
+///         Loaded(Vec<u8>),
+///         Moved { x: i32, y: i32 },
+///         Closed,
+///     }
+/// }
+///
+/// use variant_set::{impl_variant_enum, VariantSet};
+///
+/// impl_variant_enum! {
+///     /// An event from the remote crate.
+///     #[derive(Debug, PartialEq)]
+///     pub struct Event(remote::Event) => pub enum EventVariant {
+///         Loaded(..),
+///         Moved { .. },
+///         Closed,
+///     }
+/// }
+///
+/// let mut set = VariantSet::new();
+/// set.set(Event(remote::Event::Moved { x: 1, y: 2 }));
+/// set.set(Event::from(remote::Event::Closed));
+/// set.set(Event(remote::Event::Moved { x: 3, y: 4 }));
+///
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set.get(EventVariant::Moved), Some(&Event(remote::Event::Moved { x: 3, y: 4 })));
+///
+/// let mut names: Vec<_> = set.iter_with_info().map(|(info, _)| (info.index, info.name)).collect();
+/// names.sort();
+/// assert_eq!(names, [(1, "Moved"), (2, "Closed")]);
+///
+/// let moved: Option<remote::Event> = set.take(EventVariant::Moved).map(Into::into);
+/// assert_eq!(moved, Some(remote::Event::Moved { x: 3, y: 4 }));
+/// ```
+///
+/// For an enum of the current crate, for example one generated by another macro, the trait can be implemented
+/// directly by naming the enum instead:
+///
+/// ```
+/// use variant_set::{impl_variant_enum, VariantSet};
+///
+/// pub enum Message {
+///     Ping,
+///     Text(String),
+/// }
+///
+/// impl_variant_enum! {
+///     Message => pub enum MessageVariant {
+///         Ping,
+///         Text(..),
+///     }
+/// }
+///
+/// let set = VariantSet::from([Message::Ping, Message::Text("Hello".to_string())]);
+/// assert!(set.contains(MessageVariant::Text));
+/// ```
+#[macro_export]
+macro_rules! impl_variant_enum {
+    (
+        @impl $ty:ty, $enum:ty, |$value:ident| $inner:expr,
+        $(#[$variant_meta:meta])* $variant_vis:vis enum $variant_name:ident {
+            $($(#[$case_meta:meta])* $case:ident $(($($tuple:tt)*))? $({$($named:tt)*})?),*
+        }
+    ) => {
+        $(#[$variant_meta])*
+        #[derive(
+            ::core::fmt::Debug,
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash,
+            ::core::cmp::PartialOrd,
+            ::core::cmp::Ord
+        )]
+        $variant_vis enum $variant_name {
+            $($(#[$case_meta])* $case),*
+        }
+
+        impl $crate::VariantKey for $variant_name {
+            type BuildHasher = $crate::DenseIndex;
+        }
+
+        impl $crate::VariantKind for $variant_name {
+            type Kind = Self;
+
+            fn kind(self) -> Self {
+                self
+            }
+        }
+
+        #[allow(deprecated)]
+        impl $crate::FiniteKey for $variant_name {
+            const COUNT: ::core::primitive::usize = Self::ALL.len();
+            const ALL: &'static [Self] = &[$(Self::$case),*];
+
+            fn index(self) -> ::core::primitive::usize {
+                self as ::core::primitive::usize
+            }
+
+            fn name(self) -> &'static ::core::primitive::str {
+                match self {
+                    $(Self::$case => ::core::stringify!($case),)*
+                }
+            }
+        }
+
+        #[allow(deprecated)]
+        impl $crate::VariantReflect for $variant_name {
+            const VARIANTS: &'static [$crate::VariantInfo] = &[$(
+                $crate::VariantInfo {
+                    name: ::core::stringify!($case),
+                    index: Self::$case as ::core::primitive::usize,
+                    docs: "",
+                    fields: &[],
+                }
+            ),*];
+
+            fn variant_index(self) -> ::core::primitive::usize {
+                self as ::core::primitive::usize
+            }
+        }
+
+        impl ::core::convert::From<$ty> for $variant_name {
+            fn from(value: $ty) -> Self {
+                <$ty as $crate::VariantEnum>::variant(&value)
+            }
+        }
+
+        impl $crate::VariantEnum for $ty {
+            type Variant = $variant_name;
+
+            fn variant(&self) -> Self::Variant {
+                type Enum = $enum;
+
+                let $value = self;
+                match $inner {
+                    $(Enum::$case $(($($tuple)*))? $({$($named)*})? => $variant_name::$case,)*
+                }
+            }
+        }
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($foreign:ty) => $(#[$variant_meta:meta])* $variant_vis:vis enum $variant_name:ident {
+            $($(#[$case_meta:meta])* $case:ident $(($($tuple:tt)*))? $({$($named:tt)*})?),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name(pub $foreign);
+
+        impl ::core::convert::From<$foreign> for $name {
+            fn from(value: $foreign) -> Self {
+                Self(value)
+            }
+        }
+
+        impl ::core::convert::From<$name> for $foreign {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        $crate::impl_variant_enum! {
+            @impl $name, $foreign, |value| &value.0,
+            $(#[$variant_meta])* $variant_vis enum $variant_name { $($(#[$case_meta])* $case $(($($tuple)*))? $({$($named)*})?),* }
+        }
+    };
+
+    (
+        $ty:ty => $(#[$variant_meta:meta])* $variant_vis:vis enum $variant_name:ident {
+            $($(#[$case_meta:meta])* $case:ident $(($($tuple:tt)*))? $({$($named:tt)*})?),* $(,)?
+        }
+    ) => {
+        $crate::impl_variant_enum! {
+            @impl $ty, $ty, |value| value,
+            $(#[$variant_meta])* $variant_vis enum $variant_name { $($(#[$case_meta])* $case $(($($tuple)*))? $({$($named)*})?),* }
+        }
+    };
+}
@@ -0,0 +1,378 @@
+use crate::{VariantEnum, VariantSet, VariantStorage};
+
+/// A trait for enums with a variant wrapping a single value of type `P`, so that values can be looked up by the type
+/// of their payload.
+///
+/// `#[derive(VariantEnum)]` implements this trait for every tuple variant with a single field whose type, as written,
+/// is not used by any other single-field tuple variant. Variants with key fields are skipped.
+///
+/// Since types are compared as written, the same type under two paths, such as `Position` and `crate::Position`, or
+/// through a type alias, is not recognized as a duplicate, and the two implementations conflict. Marking one of the
+/// variants with `#[variant(no_variant_of)]` leaves it out.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantOf};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Component {
+///     Position(Position),
+///     Velocity(Velocity),
+///     Hidden,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Position(f32, f32);
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Velocity(f32, f32);
+///
+/// let component = Component::from_payload(Position(1.0, 2.0));
+/// assert_eq!(component, Component::Position(Position(1.0, 2.0)));
+/// assert_eq!(<Component as VariantOf<Position>>::VARIANT, ComponentVariant::Position);
+///
+/// let payload: Option<&Velocity> = component.payload();
+/// assert_eq!(payload, None);
+/// ```
+///
+/// Variants wrapping the same type do not implement this trait, and a type under another name must be left out:
+/// ```
+/// use variant_set::{VariantEnum, VariantSet};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Component {
+///     Position(Position),
+///     // `Width` and `Height` both wrap a `u32`, so neither implements `VariantOf<u32>`.
+///     Width(u32),
+///     Height(u32),
+///     // The same type as `Position`, under another name.
+///     #[variant(no_variant_of)]
+///     Target(Point),
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Position { x: f32, y: f32 }
+///
+/// type Point = Position;
+///
+/// let mut set = VariantSet::new();
+/// set.insert_payload(Position { x: 0.0, y: 0.0 });
+/// set.set(Component::Target(Point { x: 1.0, y: 1.0 }));
+///
+/// assert_eq!(set.get_by_type::<Position>(), Some(&Position { x: 0.0, y: 0.0 }));
+/// assert!(set.contains(ComponentVariant::Target));
+/// ```
+pub trait VariantOf<P>: VariantEnum + Sized {
+    /// The variant wrapping a `P`.
+    const VARIANT: Self::Variant;
+
+    /// Wraps a payload into its variant.
+    fn from_payload(payload: P) -> Self;
+
+    /// Returns a reference to the payload, if the value is of the variant wrapping a `P`.
+    fn payload(&self) -> Option<&P>;
+
+    /// Returns a mutable reference to the payload, if the value is of the variant wrapping a `P`.
+    fn payload_mut(&mut self) -> Option<&mut P>;
+
+    /// Unwraps the payload, if the value is of the variant wrapping a `P`.
+    fn into_payload(self) -> Option<P>;
+}
+
+/// A trait for zero-sized types standing for a variant of an enum, so that a variant can be named in a type parameter
+/// and its payload type is known at compile time.
+///
+/// Unlike `VariantOf`, which finds the variant from the payload type, a marker names the variant itself, so it also
+/// works for variants wrapping the same type. `#[variant_enum(markers)]` on a `#[derive(VariantEnum)]` enum generates
+/// a marker for every variant with a single field and no key fields, in a `markers` module nested in a module named
+/// after the enum in snake case.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantMarker};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// #[variant_enum(markers)]
+/// enum Component {
+///     Width(u32),
+///     Height(u32),
+/// }
+///
+/// use component::markers;
+///
+/// let component = markers::Height::from_payload(10);
+/// assert_eq!(component, Component::Height(10));
+/// assert_eq!(markers::Height::VARIANT, ComponentVariant::Height);
+///
+/// assert_eq!(markers::Width::payload(&component), None);
+/// assert_eq!(markers::Height::into_payload(component), Some(10));
+/// ```
+pub trait VariantMarker {
+    /// The enum of the variant.
+    type Enum: VariantEnum;
+
+    /// The type of the data wrapped by the variant.
+    type Payload;
+
+    /// The variant the marker stands for.
+    const VARIANT: <Self::Enum as VariantEnum>::Variant;
+
+    /// Wraps a payload into the variant.
+    fn from_payload(payload: Self::Payload) -> Self::Enum;
+
+    /// Returns a reference to the payload, if the value is of the variant.
+    fn payload(value: &Self::Enum) -> Option<&Self::Payload>;
+
+    /// Returns a mutable reference to the payload, if the value is of the variant.
+    fn payload_mut(value: &mut Self::Enum) -> Option<&mut Self::Payload>;
+
+    /// Unwraps the payload, if the value is of the variant.
+    fn into_payload(value: Self::Enum) -> Option<Self::Payload>;
+}
+
+impl<T, S> VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    /// Returns a reference to the payload of type `P`, if the set contains the variant wrapping it.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Component {
+    ///     Position(Position),
+    ///     Velocity(Velocity),
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Position(f32, f32);
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Velocity(f32, f32);
+    ///
+    /// let set = VariantSet::from([Component::Position(Position(1.0, 2.0))]);
+    ///
+    /// assert_eq!(set.get_by_type::<Position>(), Some(&Position(1.0, 2.0)));
+    /// assert_eq!(set.get_by_type::<Velocity>(), None);
+    /// ```
+    pub fn get_by_type<P>(&self) -> Option<&P>
+    where
+        T: VariantOf<P>,
+    {
+        self.get(T::VARIANT).and_then(T::payload)
+    }
+
+    /// Adds a payload to the set, wrapped in its variant, replacing the value of the same variant. Returns the
+    /// replaced value, if any.
+    ///
+    /// The replaced value is returned whole rather than as a payload, since it can be of another variant if the
+    /// variant is shared with other variants through `#[variant(slot = "...")]`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Component {
+    ///     Position(Position),
+    ///     Velocity(Velocity),
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Position(f32, f32);
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Velocity(f32, f32);
+    ///
+    /// let mut set = VariantSet::new();
+    /// assert_eq!(set.insert_payload(Position(1.0, 2.0)), None);
+    /// assert_eq!(set.insert_payload(Position(3.0, 4.0)), Some(Component::Position(Position(1.0, 2.0))));
+    ///
+    /// assert_eq!(set.get(ComponentVariant::Position), Some(&Component::Position(Position(3.0, 4.0))));
+    /// ```
+    ///
+    /// Adding the payload of a variant in a slot replaces the other variant of the slot:
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Shape {
+    ///     #[variant(slot = "Outline")]
+    ///     Circle(Circle),
+    ///     #[variant(slot = "Outline")]
+    ///     Square(Square),
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Circle(u32);
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Square(u32);
+    ///
+    /// let mut set = VariantSet::from([Shape::Square(Square(1))]);
+    ///
+    /// assert_eq!(set.insert_payload(Circle(2)), Some(Shape::Square(Square(1))));
+    /// assert_eq!(set.get(ShapeVariant::Outline), Some(&Shape::Circle(Circle(2))));
+    /// ```
+    pub fn insert_payload<P>(&mut self, payload: P) -> Option<T>
+    where
+        T: VariantOf<P>,
+    {
+        self.set(T::from_payload(payload))
+    }
+
+    /// Removes the value of the variant wrapping a `P` from the set, and returns its payload.
+    ///
+    /// Nothing is removed if the variant is shared with other variants through `#[variant(slot = "...")]`, and the
+    /// value in the set is of another variant.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum Component {
+    ///     Position(Position),
+    ///     Velocity(Velocity),
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Position(f32, f32);
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Velocity(f32, f32);
+    ///
+    /// let mut set = VariantSet::from([
+    ///     Component::Position(Position(1.0, 2.0)),
+    ///     Component::Velocity(Velocity(0.5, 0.0)),
+    /// ]);
+    ///
+    /// assert_eq!(set.remove_by_type::<Velocity>(), Some(Velocity(0.5, 0.0)));
+    /// assert_eq!(set.remove_by_type::<Velocity>(), None);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn remove_by_type<P>(&mut self) -> Option<P>
+    where
+        T: VariantOf<P>,
+    {
+        self.get_by_type::<P>()?;
+        self.remove(T::VARIANT).and_then(T::into_payload)
+    }
+
+    /// Returns a reference to the payload of the variant named by the marker `M`, if the set contains it.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// #[variant_enum(markers)]
+    /// enum Component {
+    ///     Width(u32),
+    ///     Height(u32),
+    /// }
+    ///
+    /// use component::markers;
+    ///
+    /// let set = VariantSet::from([Component::Width(20)]);
+    ///
+    /// assert_eq!(set.get_typed::<markers::Width>(), Some(&20));
+    /// assert_eq!(set.get_typed::<markers::Height>(), None);
+    /// ```
+    pub fn get_typed<M>(&self) -> Option<&M::Payload>
+    where
+        M: VariantMarker<Enum = T>,
+    {
+        self.get(M::VARIANT).and_then(M::payload)
+    }
+
+    /// Adds a payload to the set, wrapped in the variant named by the marker `M`, replacing the value of the same
+    /// variant. Returns the replaced value, if any.
+    ///
+    /// As with `insert_payload`, the replaced value is returned whole, since it can be of another variant of the same
+    /// slot.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// #[variant_enum(markers)]
+    /// enum Component {
+    ///     Width(u32),
+    ///     Height(u32),
+    /// }
+    ///
+    /// use component::markers;
+    ///
+    /// let mut set = VariantSet::new();
+    /// assert_eq!(set.set_typed::<markers::Height>(10), None);
+    /// assert_eq!(set.set_typed::<markers::Height>(15), Some(Component::Height(10)));
+    ///
+    /// assert_eq!(set.get(ComponentVariant::Height), Some(&Component::Height(15)));
+    /// assert_eq!(set.get(ComponentVariant::Width), None);
+    /// ```
+    ///
+    /// Setting a variant in a slot replaces the other variant of the slot:
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// #[variant_enum(markers)]
+    /// enum Shape {
+    ///     #[variant(slot = "Outline")]
+    ///     Circle(u32),
+    ///     #[variant(slot = "Outline")]
+    ///     Square(u32),
+    /// }
+    ///
+    /// use shape::markers;
+    ///
+    /// let mut set = VariantSet::from([Shape::Square(1)]);
+    ///
+    /// assert_eq!(set.set_typed::<markers::Circle>(2), Some(Shape::Square(1)));
+    /// assert_eq!(set.get_typed::<markers::Circle>(), Some(&2));
+    /// assert_eq!(set.get_typed::<markers::Square>(), None);
+    /// ```
+    pub fn set_typed<M>(&mut self, payload: M::Payload) -> Option<T>
+    where
+        M: VariantMarker<Enum = T>,
+    {
+        self.set(M::from_payload(payload))
+    }
+
+    /// Removes the value of the variant named by the marker `M` from the set, and returns its payload.
+    ///
+    /// Nothing is removed if the variant is shared with other variants through `#[variant(slot = "...")]`, and the
+    /// value in the set is of another variant.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// #[variant_enum(markers)]
+    /// enum Component {
+    ///     Width(u32),
+    ///     Height(u32),
+    /// }
+    ///
+    /// use component::markers;
+    ///
+    /// let mut set = VariantSet::from([Component::Width(20), Component::Height(10)]);
+    ///
+    /// assert_eq!(set.remove_typed::<markers::Width>(), Some(20));
+    /// assert_eq!(set.remove_typed::<markers::Width>(), None);
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn remove_typed<M>(&mut self) -> Option<M::Payload>
+    where
+        M: VariantMarker<Enum = T>,
+    {
+        self.get_typed::<M>()?;
+        self.remove(M::VARIANT).and_then(M::into_payload)
+    }
+}
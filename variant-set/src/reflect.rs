@@ -0,0 +1,70 @@
+use crate::VariantKey;
+
+/// Describes a variant of an enum deriving `VariantEnum`, as returned by `VariantReflect::info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VariantInfo {
+    /// The name of the variant in the variant enum.
+    pub name: &'static str,
+
+    /// The position of the variant in the variant enum, starting at 0.
+    pub index: usize,
+
+    /// The doc comment of the variant, without the leading `///`, or an empty string.
+    pub docs: &'static str,
+
+    /// The fields of the variant. Variants shared by several variants with `#[variant(slot = "...")]` have no fields.
+    pub fields: &'static [FieldInfo],
+}
+
+/// Describes a field of a variant, as part of a `VariantInfo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldInfo {
+    /// The name of the field, or its position for tuple variants.
+    pub name: &'static str,
+
+    /// The type of the field, as written in the source.
+    pub type_name: &'static str,
+}
+
+/// A trait for variant keys that describe their variants at runtime.
+///
+/// `#[derive(VariantEnum)]` and `impl_variant_enum!` implement this trait for every variant enum they generate, so that
+/// tools such as inspectors or loggers can show any `VariantSet` without code specific to its enum.
+///
+/// # Examples
+/// ```
+/// use variant_set::{FieldInfo, VariantEnum, VariantReflect};
+///
+/// #[derive(VariantEnum)]
+/// enum Shape {
+///     /// A circle around the origin.
+///     Circle { radius: f64 },
+///     Polygon(Vec<(f32, f32)>),
+///     Empty,
+/// }
+///
+/// let info = ShapeVariant::Circle.info();
+/// assert_eq!(info.name, "Circle");
+/// assert_eq!(info.index, 0);
+/// assert_eq!(info.docs, "A circle around the origin.");
+/// assert_eq!(info.fields, &[FieldInfo { name: "radius", type_name: "f64" }]);
+///
+/// assert_eq!(ShapeVariant::Polygon.variant_index(), 1);
+/// let info = ShapeVariant::Polygon.info();
+/// assert_eq!(info.fields, &[FieldInfo { name: "0", type_name: "Vec<(f32, f32)>" }]);
+///
+/// let names: Vec<_> = ShapeVariant::VARIANTS.iter().map(|info| info.name).collect();
+/// assert_eq!(names, ["Circle", "Polygon", "Empty"]);
+/// ```
+pub trait VariantReflect: VariantKey {
+    /// The descriptions of all variants, in declaration order.
+    const VARIANTS: &'static [VariantInfo];
+
+    /// Returns the position of this variant in `VARIANTS`.
+    fn variant_index(self) -> usize;
+
+    /// Returns the description of this variant.
+    fn info(self) -> &'static VariantInfo {
+        &Self::VARIANTS[self.variant_index()]
+    }
+}
@@ -0,0 +1,444 @@
+use std::{fmt, iter::FusedIterator, mem, slice, vec};
+
+use crate::{FiniteKey, VariantEnum, VariantSet, VariantStorage};
+
+/// The number of variants tracked by each word of the presence bitmap.
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A set of enum variants for enums with many variants, of which only a few are usually present.
+///
+/// This is a `VariantSet` backed by a `SparseStorage`, which stores values in one of two layouts, chosen from the
+/// number of variants of the enum and the number of values:
+///
+/// - a sparse layout, with a presence bitmap of one bit per variant and a `Vec` of the present values sorted by variant,
+///   where the position of a value is found by counting the bits set before its variant;
+/// - a dense layout, with one `Option<T>` per variant.
+///
+/// The storage starts sparse, switches to the dense layout once at least a quarter of the variants are present, and
+/// back to the sparse layout once less than an eighth of them are. Unlike with the default storage, iteration is always
+/// in declaration order of the variants.
+///
+/// # Examples
+/// ```
+/// use variant_set::{SparseVariantSet, VariantEnum};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum DhcpOption {
+///     Mtu(u16),
+///     Timeout(u32),
+///     Name(String),
+///     Flags(u8),
+///     Retries(u8),
+///     Window(u32),
+///     Cookie(u64),
+///     Trace,
+///     Compress(bool),
+/// }
+///
+/// let mut set = SparseVariantSet::default();
+/// set.set(DhcpOption::Timeout(30));
+/// set.set(DhcpOption::Mtu(1500));
+/// assert!(!set.storage().is_dense());
+///
+/// set.set(DhcpOption::Trace);
+/// assert!(set.storage().is_dense());
+///
+/// assert_eq!(set.get(DhcpOptionVariant::Mtu), Some(&DhcpOption::Mtu(1500)));
+/// assert_eq!(
+///     set.iter().collect::<Vec<_>>(),
+///     [&DhcpOption::Mtu(1500), &DhcpOption::Timeout(30), &DhcpOption::Trace],
+/// );
+///
+/// set.remove(DhcpOptionVariant::Trace);
+/// set.remove(DhcpOptionVariant::Mtu);
+/// assert!(!set.storage().is_dense());
+/// assert_eq!(set.len(), 1);
+/// ```
+pub type SparseVariantSet<T> = VariantSet<T, SparseStorage<T>>;
+
+/// The storage of a `SparseVariantSet`, switching between a sparse and a dense layout. See `SparseVariantSet`.
+pub struct SparseStorage<T>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+{
+    layout: Layout<T>,
+}
+
+#[derive(Clone)]
+enum Layout<T> {
+    Sparse {
+        /// One bit per variant, empty until the first insertion.
+        bitmap: Vec<u64>,
+        /// The present values, sorted by the index of their variant.
+        values: Vec<T>,
+    },
+    Dense {
+        slots: Box<[Option<T>]>,
+        len: usize,
+    },
+}
+
+impl<T> SparseStorage<T>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+{
+    /// Creates a new, sparse and empty storage. It does not allocate until a value is inserted.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            layout: Layout::Sparse {
+                bitmap: Vec::new(),
+                values: Vec::new(),
+            },
+        }
+    }
+
+    /// Returns `true` if the storage currently uses the dense layout.
+    #[must_use]
+    pub fn is_dense(&self) -> bool {
+        matches!(self.layout, Layout::Dense { .. })
+    }
+
+    fn make_dense(&mut self) {
+        let Layout::Sparse { values, .. } = &mut self.layout else {
+            return;
+        };
+        let len = values.len();
+        let mut slots = (0..T::Variant::COUNT).map(|_| None).collect::<Box<[_]>>();
+        for value in values.drain(..) {
+            let index = value.variant().index();
+            slots[index] = Some(value);
+        }
+        self.layout = Layout::Dense { slots, len };
+    }
+
+    fn make_sparse(&mut self) {
+        let Layout::Dense { slots, .. } = &mut self.layout else {
+            return;
+        };
+        let mut bitmap = vec![0; T::Variant::COUNT.div_ceil(WORD_BITS)];
+        let mut values = Vec::new();
+        for (index, slot) in slots.iter_mut().enumerate() {
+            if let Some(value) = slot.take() {
+                bitmap[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+                values.push(value);
+            }
+        }
+        self.layout = Layout::Sparse { bitmap, values };
+    }
+}
+
+impl<T> VariantStorage<T> for SparseStorage<T>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+{
+    type Iter<'a>
+        = SparseIter<'a, T>
+    where
+        T: 'a;
+    type IterMut<'a>
+        = SparseIterMut<'a, T>
+    where
+        T: 'a;
+    type IntoIter = SparseIntoIter<T>;
+    type Drain<'a>
+        = SparseIntoIter<T>
+    where
+        T: 'a;
+
+    fn len(&self) -> usize {
+        match &self.layout {
+            Layout::Sparse { values, .. } => values.len(),
+            Layout::Dense { len, .. } => *len,
+        }
+    }
+
+    fn get(&self, variant: T::Variant) -> Option<&T> {
+        let index = variant.index();
+        match &self.layout {
+            Layout::Sparse { bitmap, values } => {
+                is_set(bitmap, index).then(|| &values[rank(bitmap, index)])
+            }
+            Layout::Dense { slots, .. } => slots[index].as_ref(),
+        }
+    }
+
+    fn get_mut(&mut self, variant: T::Variant) -> Option<&mut T> {
+        let index = variant.index();
+        match &mut self.layout {
+            Layout::Sparse { bitmap, values } => {
+                is_set(bitmap, index).then(|| &mut values[rank(bitmap, index)])
+            }
+            Layout::Dense { slots, .. } => slots[index].as_mut(),
+        }
+    }
+
+    fn get_or_insert_with<F: FnOnce() -> T>(&mut self, variant: T::Variant, f: F) -> &mut T {
+        // The layout is switched before inserting, so that the returned reference points into the final layout.
+        if !self.is_dense()
+            && self.get(variant).is_none()
+            && (self.len() + 1) * 4 >= T::Variant::COUNT
+        {
+            self.make_dense();
+        }
+
+        let index = variant.index();
+        match &mut self.layout {
+            Layout::Sparse { bitmap, values } => {
+                if bitmap.is_empty() {
+                    *bitmap = vec![0; T::Variant::COUNT.div_ceil(WORD_BITS)];
+                }
+                let position = rank(bitmap, index);
+                if !is_set(bitmap, index) {
+                    bitmap[index / WORD_BITS] |= 1 << (index % WORD_BITS);
+                    values.insert(position, f());
+                }
+                &mut values[position]
+            }
+            Layout::Dense { slots, len } => {
+                let slot = &mut slots[index];
+                if slot.is_none() {
+                    *len += 1;
+                }
+                slot.get_or_insert_with(f)
+            }
+        }
+    }
+
+    fn insert(&mut self, variant: T::Variant, value: T) -> Option<T> {
+        if let Some(previous) = self.get_mut(variant) {
+            return Some(mem::replace(previous, value));
+        }
+        self.get_or_insert_with(variant, || value);
+        None
+    }
+
+    fn remove(&mut self, variant: T::Variant) -> Option<T> {
+        let index = variant.index();
+        let removed = match &mut self.layout {
+            Layout::Sparse { bitmap, values } => {
+                if !is_set(bitmap, index) {
+                    return None;
+                }
+                bitmap[index / WORD_BITS] &= !(1 << (index % WORD_BITS));
+                Some(values.remove(rank(bitmap, index)))
+            }
+            Layout::Dense { slots, len } => {
+                let removed = slots[index].take();
+                if removed.is_some() {
+                    *len -= 1;
+                }
+                removed
+            }
+        };
+
+        if self.is_dense() && self.len() * 8 < T::Variant::COUNT {
+            self.make_sparse();
+        }
+        removed
+    }
+
+    /// Removes all values, and switches back to the sparse layout.
+    fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        let entries = match &self.layout {
+            Layout::Sparse { values, .. } => Entries::Sparse(values.iter()),
+            Layout::Dense { slots, .. } => Entries::Dense(slots.iter()),
+        };
+        SparseIter {
+            entries,
+            len: self.len(),
+        }
+    }
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        let len = self.len();
+        let entries = match &mut self.layout {
+            Layout::Sparse { values, .. } => Entries::Sparse(values.iter_mut()),
+            Layout::Dense { slots, .. } => Entries::Dense(slots.iter_mut()),
+        };
+        SparseIterMut { entries, len }
+    }
+
+    fn into_values(self) -> Self::IntoIter {
+        let len = self.len();
+        let entries = match self.layout {
+            Layout::Sparse { values, .. } => Entries::Sparse(values.into_iter()),
+            Layout::Dense { slots, .. } => Entries::Dense(slots.into_vec().into_iter()),
+        };
+        SparseIntoIter { entries, len }
+    }
+
+    /// Removes all values and returns them as an iterator. The storage is empty and sparse right away.
+    fn drain(&mut self) -> Self::Drain<'_> {
+        mem::take(self).into_values()
+    }
+}
+
+/// Returns `true` if the bit of the variant with the given index is set.
+fn is_set(bitmap: &[u64], index: usize) -> bool {
+    bitmap
+        .get(index / WORD_BITS)
+        .is_some_and(|word| word & (1 << (index % WORD_BITS)) != 0)
+}
+
+/// Returns the number of bits set before the given index, which is the position of its value.
+fn rank(bitmap: &[u64], index: usize) -> usize {
+    let word = index / WORD_BITS;
+    let before = bitmap
+        .iter()
+        .take(word)
+        .map(|word| word.count_ones() as usize)
+        .sum::<usize>();
+    let mask = (1 << (index % WORD_BITS)) - 1;
+    before
+        + bitmap
+            .get(word)
+            .map_or(0, |word| (word & mask).count_ones() as usize)
+}
+
+impl<T> Default for SparseStorage<T>
+where
+    T: VariantEnum,
+    T::Variant: FiniteKey,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for SparseStorage<T>
+where
+    T: VariantEnum + Clone,
+    T::Variant: FiniteKey,
+{
+    fn clone(&self) -> Self {
+        Self {
+            layout: self.layout.clone(),
+        }
+    }
+}
+
+/// The values of either layout: the present values of the sparse layout, or the slots of the dense layout.
+#[derive(Clone)]
+enum Entries<S, D> {
+    Sparse(S),
+    Dense(D),
+}
+
+/// Implements the iterator traits for an iterator over `Entries`, which counts the values it has left in `len`.
+macro_rules! impl_sparse_iter {
+    ($name:ident<$($lifetime:lifetime,)? $t:ident>, $item:ty) => {
+        impl<$($lifetime,)? $t> Iterator for $name<$($lifetime,)? $t> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let next = match &mut self.entries {
+                    Entries::Sparse(values) => values.next(),
+                    Entries::Dense(slots) => slots.find_map(Into::into),
+                };
+                self.len -= usize::from(next.is_some());
+                next
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.len, Some(self.len))
+            }
+        }
+
+        impl<$($lifetime,)? $t> DoubleEndedIterator for $name<$($lifetime,)? $t> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                let next = match &mut self.entries {
+                    Entries::Sparse(values) => values.next_back(),
+                    Entries::Dense(slots) => slots.rev().find_map(Into::into),
+                };
+                self.len -= usize::from(next.is_some());
+                next
+            }
+        }
+
+        impl<$($lifetime,)? $t> ExactSizeIterator for $name<$($lifetime,)? $t> {
+            fn len(&self) -> usize {
+                self.len
+            }
+        }
+
+        impl<$($lifetime,)? $t> FusedIterator for $name<$($lifetime,)? $t> {}
+
+        impl<$($lifetime,)? $t> fmt::Debug for $name<$($lifetime,)? $t>
+        where
+            $t: fmt::Debug,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match &self.entries {
+                    Entries::Sparse(values) => f.debug_list().entries(values.as_slice()).finish(),
+                    Entries::Dense(slots) => f
+                        .debug_list()
+                        .entries(slots.as_slice().iter().flatten())
+                        .finish(),
+                }
+            }
+        }
+    };
+}
+
+/// An iterator over the values of a `SparseStorage`, in declaration order of their variants.
+///
+/// This `struct` is created by `VariantStorage::iter`, and used by `VariantSet::iter`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct SparseIter<'a, T> {
+    entries: Entries<slice::Iter<'a, T>, slice::Iter<'a, Option<T>>>,
+    len: usize,
+}
+
+impl_sparse_iter!(SparseIter<'a, T>, &'a T);
+
+impl<T> Clone for SparseIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// A mutable iterator over the values of a `SparseStorage`, in declaration order of their variants.
+///
+/// This `struct` is created by `VariantStorage::iter_mut`, and used by `VariantSet::iter_mut`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct SparseIterMut<'a, T> {
+    entries: Entries<slice::IterMut<'a, T>, slice::IterMut<'a, Option<T>>>,
+    len: usize,
+}
+
+impl_sparse_iter!(SparseIterMut<'a, T>, &'a mut T);
+
+/// An owning iterator over the values of a `SparseStorage`, in declaration order of their variants.
+///
+/// This `struct` is created by `VariantStorage::into_values` and `VariantStorage::drain`, and used by the
+/// `into_iter` and `drain` methods of `VariantSet`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct SparseIntoIter<T> {
+    entries: Entries<vec::IntoIter<T>, vec::IntoIter<Option<T>>>,
+    len: usize,
+}
+
+impl_sparse_iter!(SparseIntoIter<T>, T);
+
+impl<T> Clone for SparseIntoIter<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! `VariantEnum` implementations for enums of the standard library.
+//!
+//! `Cow` is not part of this list: it already implements `VariantEnum` by forwarding to the value it points to.
+
+use std::{
+    cmp::Ordering,
+    io::ErrorKind,
+    net::{IpAddr, SocketAddr},
+    ops::Bound,
+};
+
+use crate::{DenseIndex, FiniteKey, VariantEnum, VariantKey, VariantKind};
+
+/// The variants of `Option<T>`.
+///
+/// # Examples
+/// ```
+/// use variant_set::{OptionVariant, VariantSet};
+///
+/// let mut set = VariantSet::new();
+/// set.set(Some(1));
+/// set.set(None);
+/// set.set(Some(2));
+///
+/// assert_eq!(set.get(OptionVariant::Some), Some(&Some(2)));
+/// assert_eq!(set.get(OptionVariant::None), Some(&None));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptionVariant {
+    /// `Option::None`.
+    None,
+    /// `Option::Some`.
+    Some,
+}
+
+impl VariantKey for OptionVariant {
+    type BuildHasher = DenseIndex;
+}
+
+impl<T> VariantEnum for Option<T> {
+    type Variant = OptionVariant;
+
+    fn variant(&self) -> Self::Variant {
+        match self {
+            None => OptionVariant::None,
+            Some(_) => OptionVariant::Some,
+        }
+    }
+}
+
+impl<T> From<Option<T>> for OptionVariant {
+    fn from(value: Option<T>) -> Self {
+        value.variant()
+    }
+}
+
+/// The variants of `Result<T, E>`.
+///
+/// # Examples
+/// ```
+/// use variant_set::{ResultVariant, VariantSet};
+///
+/// // Keeps the latest success and the latest failure.
+/// let mut set: VariantSet<Result<u32, String>> = VariantSet::new();
+/// set.set(Ok(1));
+/// set.set(Err("timeout".to_string()));
+/// set.set(Ok(2));
+///
+/// assert_eq!(set.get(ResultVariant::Ok), Some(&Ok(2)));
+/// assert_eq!(set.get(ResultVariant::Err), Some(&Err("timeout".to_string())));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResultVariant {
+    /// `Result::Ok`.
+    Ok,
+    /// `Result::Err`.
+    Err,
+}
+
+impl VariantKey for ResultVariant {
+    type BuildHasher = DenseIndex;
+}
+
+impl<T, E> VariantEnum for Result<T, E> {
+    type Variant = ResultVariant;
+
+    fn variant(&self) -> Self::Variant {
+        match self {
+            Ok(_) => ResultVariant::Ok,
+            Err(_) => ResultVariant::Err,
+        }
+    }
+}
+
+impl<T, E> From<Result<T, E>> for ResultVariant {
+    fn from(value: Result<T, E>) -> Self {
+        value.variant()
+    }
+}
+
+/// The variants of `IpAddr`.
+///
+/// # Examples
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+/// use variant_set::{IpAddrVariant, VariantSet};
+///
+/// let set = VariantSet::from([
+///     IpAddr::V4(Ipv4Addr::LOCALHOST),
+///     IpAddr::V6(Ipv6Addr::LOCALHOST),
+///     IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+/// ]);
+///
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set.get(IpAddrVariant::V4), Some(&IpAddr::V4(Ipv4Addr::UNSPECIFIED)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IpAddrVariant {
+    /// `IpAddr::V4`.
+    V4,
+    /// `IpAddr::V6`.
+    V6,
+}
+
+impl VariantKey for IpAddrVariant {
+    type BuildHasher = DenseIndex;
+}
+
+impl VariantEnum for IpAddr {
+    type Variant = IpAddrVariant;
+
+    fn variant(&self) -> Self::Variant {
+        match self {
+            IpAddr::V4(_) => IpAddrVariant::V4,
+            IpAddr::V6(_) => IpAddrVariant::V6,
+        }
+    }
+}
+
+impl From<IpAddr> for IpAddrVariant {
+    fn from(value: IpAddr) -> Self {
+        value.variant()
+    }
+}
+
+/// The variants of `SocketAddr`.
+///
+/// # Examples
+/// ```
+/// use std::net::SocketAddr;
+/// use variant_set::{SocketAddrVariant, VariantSet};
+///
+/// let set: VariantSet<SocketAddr> = ["127.0.0.1:80", "[::1]:80", "10.0.0.1:443"]
+///     .iter()
+///     .map(|addr| addr.parse().unwrap())
+///     .collect();
+///
+/// assert_eq!(set.get(SocketAddrVariant::V4), Some(&"10.0.0.1:443".parse().unwrap()));
+/// assert_eq!(set.get(SocketAddrVariant::V6), Some(&"[::1]:80".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SocketAddrVariant {
+    /// `SocketAddr::V4`.
+    V4,
+    /// `SocketAddr::V6`.
+    V6,
+}
+
+impl VariantKey for SocketAddrVariant {
+    type BuildHasher = DenseIndex;
+}
+
+impl VariantEnum for SocketAddr {
+    type Variant = SocketAddrVariant;
+
+    fn variant(&self) -> Self::Variant {
+        match self {
+            SocketAddr::V4(_) => SocketAddrVariant::V4,
+            SocketAddr::V6(_) => SocketAddrVariant::V6,
+        }
+    }
+}
+
+impl From<SocketAddr> for SocketAddrVariant {
+    fn from(value: SocketAddr) -> Self {
+        value.variant()
+    }
+}
+
+/// The variants of `Bound<T>`.
+///
+/// # Examples
+/// ```
+/// use std::ops::Bound;
+/// use variant_set::{BoundVariant, VariantSet};
+///
+/// let set = VariantSet::from([Bound::Included(1), Bound::Excluded(5), Bound::Unbounded]);
+///
+/// assert_eq!(set.len(), 3);
+/// assert_eq!(set.get(BoundVariant::Excluded), Some(&Bound::Excluded(5)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoundVariant {
+    /// `Bound::Included`.
+    Included,
+    /// `Bound::Excluded`.
+    Excluded,
+    /// `Bound::Unbounded`.
+    Unbounded,
+}
+
+impl VariantKey for BoundVariant {
+    type BuildHasher = DenseIndex;
+}
+
+impl<T> VariantEnum for Bound<T> {
+    type Variant = BoundVariant;
+
+    fn variant(&self) -> Self::Variant {
+        match self {
+            Bound::Included(_) => BoundVariant::Included,
+            Bound::Excluded(_) => BoundVariant::Excluded,
+            Bound::Unbounded => BoundVariant::Unbounded,
+        }
+    }
+}
+
+impl<T> From<Bound<T>> for BoundVariant {
+    fn from(value: Bound<T>) -> Self {
+        value.variant()
+    }
+}
+
+// `Ordering` and `ErrorKind` carry no data, so they are their own variants.
+
+impl VariantKey for Ordering {
+    type BuildHasher = DenseIndex;
+}
+
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use variant_set::VariantSet;
+///
+/// let set: VariantSet<Ordering> = [1, 5, 3].iter().map(|n| n.cmp(&3)).collect();
+///
+/// assert!(set.contains(Ordering::Less));
+/// assert!(set.contains(Ordering::Equal));
+/// assert!(set.contains(Ordering::Greater));
+/// ```
+impl VariantEnum for Ordering {
+    type Variant = Ordering;
+
+    fn variant(&self) -> Self::Variant {
+        *self
+    }
+}
+
+impl VariantKey for ErrorKind {
+    type BuildHasher = DenseIndex;
+}
+
+/// # Examples
+/// ```
+/// use std::io::ErrorKind;
+/// use variant_set::VariantSet;
+///
+/// let mut set = VariantSet::new();
+/// set.set(ErrorKind::NotFound);
+/// set.set(ErrorKind::TimedOut);
+/// set.set(ErrorKind::NotFound);
+///
+/// assert_eq!(set.len(), 2);
+/// assert!(set.contains(ErrorKind::TimedOut));
+/// ```
+impl VariantEnum for ErrorKind {
+    type Variant = ErrorKind;
+
+    fn variant(&self) -> Self::Variant {
+        *self
+    }
+}
+
+/// Implements `VariantKind` for variant keys without data, which are their own kind.
+macro_rules! impl_own_kind {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl VariantKind for $ty {
+                type Kind = Self;
+
+                fn kind(self) -> Self {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_own_kind!(
+    OptionVariant,
+    ResultVariant,
+    IpAddrVariant,
+    SocketAddrVariant,
+    BoundVariant,
+    Ordering,
+    ErrorKind,
+);
+
+/// Implements `FiniteKey` for the variant keys defined in this module.
+macro_rules! impl_finite_key {
+    ($($ty:ident { $($case:ident),* }),* $(,)?) => {
+        $(
+            impl FiniteKey for $ty {
+                const COUNT: usize = Self::ALL.len();
+                const ALL: &'static [Self] = &[$(Self::$case),*];
+
+                fn index(self) -> usize {
+                    self as usize
+                }
+
+                fn name(self) -> &'static str {
+                    match self {
+                        $(Self::$case => stringify!($case),)*
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_finite_key!(
+    OptionVariant { None, Some },
+    ResultVariant { Ok, Err },
+    IpAddrVariant { V4, V6 },
+    SocketAddrVariant { V4, V6 },
+    BoundVariant {
+        Included,
+        Excluded,
+        Unbounded
+    },
+);
@@ -0,0 +1,402 @@
+use std::{collections::TryReserveError, fmt, iter::FusedIterator};
+
+use indexmap::{map, IndexMap};
+
+use crate::{VariantEnum, VariantHasher};
+
+/// The storage used by `VariantSet<T>` when no storage is given, a `HashStorage<T>`.
+pub type DefaultStorage<T> = HashStorage<T>;
+
+/// A trait for the backends storing the values of a `VariantSet`, at most one for each variant.
+///
+/// `VariantSet<T, S>` implements all of its methods on top of this trait, so any storage can be plugged in as the
+/// second type parameter. The crate provides `HashStorage`, the default, and `SparseStorage`, for enums with many
+/// variants of which few are present.
+///
+/// A storage is only responsible for storing values by variant: it can assume that every value it is given is of the
+/// variant it is stored under. The capacity methods have default implementations for storages without a notion of
+/// capacity.
+///
+/// Storages that cannot be created from nothing, such as ones allocating from an arena, do not need to implement
+/// `Default`. Sets using them are created with `VariantSet::from_storage`, while `Default`, `FromIterator` and
+/// `VariantSet::decode` require a storage implementing `Default`.
+///
+/// # Examples
+/// A storage that keeps the values in a `Vec` and finds them by scanning it, which is fast for small sets:
+/// ```
+/// use std::{slice, vec};
+/// use variant_set::{VariantEnum, VariantSet, VariantStorage};
+///
+/// struct VecStorage<T>(Vec<T>);
+///
+/// impl<T> Default for VecStorage<T> {
+///     fn default() -> Self {
+///         Self(Vec::new())
+///     }
+/// }
+///
+/// impl<T: VariantEnum> VariantStorage<T> for VecStorage<T> {
+///     type Iter<'a> = slice::Iter<'a, T> where T: 'a;
+///     type IterMut<'a> = slice::IterMut<'a, T> where T: 'a;
+///     type IntoIter = vec::IntoIter<T>;
+///     type Drain<'a> = vec::Drain<'a, T> where T: 'a;
+///
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+///
+///     fn get(&self, variant: T::Variant) -> Option<&T> {
+///         self.0.iter().find(|value| value.variant() == variant)
+///     }
+///
+///     fn get_mut(&mut self, variant: T::Variant) -> Option<&mut T> {
+///         self.0.iter_mut().find(|value| value.variant() == variant)
+///     }
+///
+///     fn get_or_insert_with<F: FnOnce() -> T>(&mut self, variant: T::Variant, f: F) -> &mut T {
+///         let position = match self.0.iter().position(|value| value.variant() == variant) {
+///             Some(position) => position,
+///             None => {
+///                 self.0.push(f());
+///                 self.0.len() - 1
+///             }
+///         };
+///         &mut self.0[position]
+///     }
+///
+///     fn insert(&mut self, variant: T::Variant, value: T) -> Option<T> {
+///         match self.get_mut(variant) {
+///             Some(previous) => Some(std::mem::replace(previous, value)),
+///             None => {
+///                 self.0.push(value);
+///                 None
+///             }
+///         }
+///     }
+///
+///     fn remove(&mut self, variant: T::Variant) -> Option<T> {
+///         let position = self.0.iter().position(|value| value.variant() == variant)?;
+///         Some(self.0.swap_remove(position))
+///     }
+///
+///     fn clear(&mut self) {
+///         self.0.clear();
+///     }
+///
+///     fn iter(&self) -> Self::Iter<'_> {
+///         self.0.iter()
+///     }
+///
+///     fn iter_mut(&mut self) -> Self::IterMut<'_> {
+///         self.0.iter_mut()
+///     }
+///
+///     fn into_values(self) -> Self::IntoIter {
+///         self.0.into_iter()
+///     }
+///
+///     fn drain(&mut self) -> Self::Drain<'_> {
+///         self.0.drain(..)
+///     }
+/// }
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum Light {
+///     Red(u8),
+///     Green(u8),
+///     Blue(u8),
+/// }
+///
+/// let mut set: VariantSet<Light, VecStorage<Light>> = VariantSet::default();
+/// set.set(Light::Red(255));
+/// set.set(Light::Blue(10));
+/// set.set(Light::Red(128));
+///
+/// assert_eq!(set.len(), 2);
+/// assert_eq!(set.get(LightVariant::Red), Some(&Light::Red(128)));
+/// assert_eq!(set.missing().collect::<Vec<_>>(), [LightVariant::Green]);
+/// ```
+pub trait VariantStorage<T>
+where
+    T: VariantEnum,
+{
+    /// The iterator returned by `iter`.
+    type Iter<'a>: DoubleEndedIterator<Item = &'a T> + ExactSizeIterator + FusedIterator + Clone
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// The iterator returned by `iter_mut`.
+    type IterMut<'a>: DoubleEndedIterator<Item = &'a mut T> + ExactSizeIterator + FusedIterator
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// The iterator returned by `into_values`.
+    type IntoIter: DoubleEndedIterator<Item = T> + ExactSizeIterator + FusedIterator;
+
+    /// The iterator returned by `drain`.
+    type Drain<'a>: DoubleEndedIterator<Item = T> + ExactSizeIterator + FusedIterator
+    where
+        Self: 'a,
+        T: 'a;
+
+    /// Returns the number of values in the storage.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the storage contains no values.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the value of the given variant, if any.
+    fn get(&self, variant: T::Variant) -> Option<&T>;
+
+    /// Returns a mutable reference to the value of the given variant, if any.
+    fn get_mut(&mut self, variant: T::Variant) -> Option<&mut T>;
+
+    /// Returns a mutable reference to the value of the given variant, storing the value returned by `f` first if the
+    /// variant is not present.
+    fn get_or_insert_with<F: FnOnce() -> T>(&mut self, variant: T::Variant, f: F) -> &mut T;
+
+    /// Stores a value under its variant, and returns the value it replaced, if any.
+    fn insert(&mut self, variant: T::Variant, value: T) -> Option<T>;
+
+    /// Removes the value of the given variant, and returns it if it existed.
+    fn remove(&mut self, variant: T::Variant) -> Option<T>;
+
+    /// Removes all values.
+    fn clear(&mut self);
+
+    /// Returns an iterator over the values, in the iteration order of the storage.
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Returns an iterator over mutable references to the values, in the same order as `iter`.
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+
+    /// Consumes the storage and returns an iterator over the values, in the same order as `iter`.
+    fn into_values(self) -> Self::IntoIter;
+
+    /// Removes all values and returns them as an iterator. The storage is empty once the iterator is dropped, even if
+    /// it was not fully consumed.
+    fn drain(&mut self) -> Self::Drain<'_>;
+
+    /// Returns the number of values the storage can hold without reallocating. Defaults to `len`.
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+
+    /// Reserves capacity for at least `additional` more values. Does nothing by default.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Tries to reserve capacity for at least `additional` more values. Does nothing by default.
+    ///
+    /// # Errors
+    /// Returns a `TryReserveError` if the allocation fails.
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let _ = additional;
+        Ok(())
+    }
+
+    /// Shrinks the capacity of the storage, keeping at least `min_capacity`. Does nothing by default.
+    fn shrink_to(&mut self, min_capacity: usize) {
+        let _ = min_capacity;
+    }
+}
+
+/// The default storage of a `VariantSet`, an `IndexMap` from variants to values hashed with the hasher chosen by the
+/// variant key.
+///
+/// It provides constant time insertion, removal and lookup for any variant key. Values are iterated in insertion
+/// order, until a value is removed, which moves the last value into its place.
+pub struct HashStorage<T>
+where
+    T: VariantEnum,
+{
+    map: IndexMap<T::Variant, T, VariantHasher<T>>,
+}
+
+impl<T> HashStorage<T>
+where
+    T: VariantEnum,
+{
+    /// Creates an empty storage with room for at least `capacity` values.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            map: IndexMap::with_capacity_and_hasher(capacity, VariantHasher::<T>::default()),
+        }
+    }
+}
+
+impl<T> Default for HashStorage<T>
+where
+    T: VariantEnum,
+{
+    fn default() -> Self {
+        Self {
+            map: IndexMap::with_hasher(VariantHasher::<T>::default()),
+        }
+    }
+}
+
+impl<T> Clone for HashStorage<T>
+where
+    T: VariantEnum + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<T> VariantStorage<T> for HashStorage<T>
+where
+    T: VariantEnum,
+{
+    type Iter<'a>
+        = map::Values<'a, T::Variant, T>
+    where
+        T: 'a;
+    type IterMut<'a>
+        = map::ValuesMut<'a, T::Variant, T>
+    where
+        T: 'a;
+    type IntoIter = map::IntoValues<T::Variant, T>;
+    type Drain<'a>
+        = HashDrain<'a, T>
+    where
+        T: 'a;
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn get(&self, variant: T::Variant) -> Option<&T> {
+        self.map.get(&variant)
+    }
+
+    fn get_mut(&mut self, variant: T::Variant) -> Option<&mut T> {
+        self.map.get_mut(&variant)
+    }
+
+    fn get_or_insert_with<F: FnOnce() -> T>(&mut self, variant: T::Variant, f: F) -> &mut T {
+        self.map.entry(variant).or_insert_with(f)
+    }
+
+    fn insert(&mut self, variant: T::Variant, value: T) -> Option<T> {
+        self.map.insert(variant, value)
+    }
+
+    fn remove(&mut self, variant: T::Variant) -> Option<T> {
+        self.map.swap_remove(&variant)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.map.values()
+    }
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.map.values_mut()
+    }
+
+    fn into_values(self) -> Self::IntoIter {
+        self.map.into_values()
+    }
+
+    fn drain(&mut self) -> Self::Drain<'_> {
+        HashDrain {
+            inner: self.map.drain(..),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        // The standard error cannot be built from the one of `IndexMap`, so the reservation is made again on a `Vec` of
+        // the same entries, which fails the same way. If it succeeds, the failure is reported as a capacity overflow.
+        self.map.try_reserve(additional).map_err(|_| {
+            let mut entries = Vec::<(T::Variant, T)>::new();
+            let error = entries.try_reserve_exact(self.map.len().saturating_add(additional));
+            error.err().unwrap_or_else(|| {
+                Vec::<u8>::new()
+                    .try_reserve_exact(usize::MAX)
+                    .expect_err("reserving more than isize::MAX bytes always fails")
+            })
+        })
+    }
+
+    fn shrink_to(&mut self, min_capacity: usize) {
+        self.map.shrink_to(min_capacity);
+    }
+}
+
+/// A draining iterator over the values of a `HashStorage`.
+///
+/// This `struct` is created by `VariantStorage::drain`, and used by `VariantSet::drain`.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct HashDrain<'a, T>
+where
+    T: VariantEnum,
+{
+    inner: map::Drain<'a, T::Variant, T>,
+}
+
+impl<T> Iterator for HashDrain<'_, T>
+where
+    T: VariantEnum,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for HashDrain<'_, T>
+where
+    T: VariantEnum,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<T> ExactSizeIterator for HashDrain<'_, T>
+where
+    T: VariantEnum,
+{
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T> FusedIterator for HashDrain<'_, T> where T: VariantEnum {}
+
+impl<T> fmt::Debug for HashDrain<'_, T>
+where
+    T: VariantEnum + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.inner.as_slice().values())
+            .finish()
+    }
+}
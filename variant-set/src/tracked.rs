@@ -0,0 +1,508 @@
+use std::{collections::HashMap, ops::Deref};
+
+use crate::{Drain, VariantEnum, VariantHasher, VariantSet};
+
+/// The changes made to a `TrackedVariantSet` since the last call to `take_changes`.
+///
+/// Changes are relative to the contents of the set when tracking last started, so a variant that was added and then
+/// removed again does not appear at all, and a variant that was removed and then added again is reported as modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantChanges<V> {
+    /// Variants that were not present before, but are now.
+    pub added: Vec<V>,
+
+    /// Variants that were present before and still are, but whose value was replaced.
+    pub modified: Vec<V>,
+
+    /// Variants that were present before, but are not anymore.
+    pub removed: Vec<V>,
+}
+
+impl<V> VariantChanges<V> {
+    /// Returns `true` if no variants were added, modified, or removed.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set: TrackedVariantSet<MyEnum> = TrackedVariantSet::new();
+    /// assert!(set.take_changes().is_empty());
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl<V> Default for VariantChanges<V> {
+    fn default() -> Self {
+        Self {
+            added: Vec::new(),
+            modified: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+/// A `VariantSet` that records which variants were inserted, replaced, or removed since the last call to `take_changes`.
+///
+/// All read-only methods of `VariantSet` are available through `Deref`. Methods that modify the set are provided by
+/// `TrackedVariantSet` itself so that every change is recorded. Use a plain `VariantSet` if you do not need change
+/// tracking, it has no tracking overhead.
+///
+/// # Examples
+/// ```
+/// use variant_set::{TrackedVariantSet, VariantEnum};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// enum MyEnum {
+///     Variant1(String),
+///     Variant2(u32),
+///     Variant3(bool),
+/// }
+///
+/// let mut set = TrackedVariantSet::new();
+/// set.set(MyEnum::Variant1("Hello".to_string()));
+/// set.set(MyEnum::Variant2(42));
+/// set.take_changes();
+///
+/// set.set(MyEnum::Variant1("World".to_string()));
+/// set.remove(MyEnumVariant::Variant2);
+/// set.set(MyEnum::Variant3(true));
+///
+/// let changes = set.take_changes();
+/// assert_eq!(changes.added, vec![MyEnumVariant::Variant3]);
+/// assert_eq!(changes.modified, vec![MyEnumVariant::Variant1]);
+/// assert_eq!(changes.removed, vec![MyEnumVariant::Variant2]);
+///
+/// assert!(set.contains(MyEnumVariant::Variant1));
+/// assert!(set.take_changes().is_empty());
+/// ```
+pub struct TrackedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    set: VariantSet<T>,
+
+    /// For every variant touched since the last `take_changes`, whether it was present before it was first touched.
+    touched: HashMap<T::Variant, bool, VariantHasher<T>>,
+}
+
+impl<T> TrackedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    /// Creates a new, empty `TrackedVariantSet`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let set: TrackedVariantSet<MyEnum> = TrackedVariantSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from(VariantSet::new())
+    }
+
+    /// Returns the changes made since the last call to `take_changes`, and starts tracking again from the current
+    /// contents of the set.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant1("Hello".to_string()));
+    ///
+    /// let changes = set.take_changes();
+    /// assert_eq!(changes.added, vec![MyEnumVariant::Variant1]);
+    /// assert!(set.take_changes().is_empty());
+    /// ```
+    pub fn take_changes(&mut self) -> VariantChanges<T::Variant> {
+        let mut changes = VariantChanges::default();
+
+        for (variant, was_present) in self.touched.drain() {
+            match (was_present, self.set.contains(variant)) {
+                (false, true) => changes.added.push(variant),
+                (true, true) => changes.modified.push(variant),
+                (true, false) => changes.removed.push(variant),
+                (false, false) => {}
+            }
+        }
+
+        changes
+    }
+
+    /// Returns `true` if any variant was inserted, replaced, or removed since the last call to `take_changes`.
+    ///
+    /// A variant that was added and then removed again still counts as a change here, even though it will not be
+    /// reported by `take_changes`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// assert!(!set.has_changes());
+    ///
+    /// set.set(MyEnum::Variant2(42));
+    /// assert!(set.has_changes());
+    /// ```
+    #[must_use]
+    pub fn has_changes(&self) -> bool {
+        !self.touched.is_empty()
+    }
+
+    /// Discards all recorded changes without returning them.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant2(42));
+    /// set.reset_changes();
+    /// assert!(!set.has_changes());
+    /// ```
+    pub fn reset_changes(&mut self) {
+        self.touched.clear();
+    }
+
+    /// Returns a reference to the underlying `VariantSet`.
+    #[must_use]
+    pub fn as_set(&self) -> &VariantSet<T> {
+        &self.set
+    }
+
+    /// Consumes the tracked set and returns the underlying `VariantSet`, discarding all recorded changes.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut tracked = TrackedVariantSet::new();
+    /// tracked.set(MyEnum::Variant2(42));
+    ///
+    /// let set: VariantSet<MyEnum> = tracked.into_inner();
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn into_inner(self) -> VariantSet<T> {
+        self.set
+    }
+
+    /// Records that `variant` is about to change, remembering whether it was present before its first change.
+    fn touch(&mut self, variant: T::Variant) {
+        let was_present = self.set.contains(variant);
+        self.touched.entry(variant).or_insert(was_present);
+    }
+
+    /// Adds a value to the set. See `VariantSet::insert`.
+    ///
+    /// Only records a change if the value was newly inserted.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant1("Hello".to_string()));
+    /// set.take_changes();
+    ///
+    /// assert!(!set.insert(MyEnum::Variant1("World".to_string())));
+    /// assert!(!set.has_changes());
+    /// ```
+    pub fn insert(&mut self, value: T) -> bool {
+        let variant = value.variant();
+        if self.set.contains(variant) {
+            return false;
+        }
+
+        self.touch(variant);
+        self.set.insert(value)
+    }
+
+    /// Sets a value in the set. If a previous value existed, it is returned. See `VariantSet::set`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant2(1));
+    /// set.take_changes();
+    ///
+    /// let previous = set.set(MyEnum::Variant2(2));
+    /// assert_eq!(previous, Some(MyEnum::Variant2(1)));
+    /// assert_eq!(set.take_changes().modified, vec![MyEnumVariant::Variant2]);
+    /// ```
+    pub fn set(&mut self, value: T) -> Option<T> {
+        self.touch(value.variant());
+        self.set.set(value)
+    }
+
+    /// Inserts the given `value` into the set if it is not present, then returns a reference to the value in the set.
+    /// See `VariantSet::get_or_insert`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// let value = set.get_or_insert(MyEnum::Variant2(42));
+    /// assert_eq!(value, &MyEnum::Variant2(42));
+    /// assert_eq!(set.take_changes().added, vec![MyEnumVariant::Variant2]);
+    /// ```
+    pub fn get_or_insert(&mut self, default: T) -> &T {
+        let variant = default.variant();
+        if !self.set.contains(variant) {
+            self.touch(variant);
+        }
+
+        self.set.get_or_insert(default)
+    }
+
+    /// Removes a variant from the set. Returns the value if it existed. See `VariantSet::remove`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant2(42));
+    /// set.take_changes();
+    ///
+    /// assert_eq!(set.remove(MyEnumVariant::Variant2), Some(MyEnum::Variant2(42)));
+    /// assert_eq!(set.take_changes().removed, vec![MyEnumVariant::Variant2]);
+    /// ```
+    pub fn remove(&mut self, value: T::Variant) -> Option<T> {
+        if !self.set.contains(value) {
+            return None;
+        }
+
+        self.touch(value);
+        self.set.remove(value)
+    }
+
+    /// Removes a variant from the set if it is equal to the given value. Returns the value if it existed.
+    /// See `VariantSet::remove_exact`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant2(42));
+    /// set.take_changes();
+    ///
+    /// assert_eq!(set.remove_exact(&MyEnum::Variant2(10)), None);
+    /// assert!(!set.has_changes());
+    /// ```
+    pub fn remove_exact(&mut self, value: &T) -> Option<T>
+    where
+        T: PartialEq,
+    {
+        if !self.set.contains_exact(value) {
+            return None;
+        }
+
+        self.touch(value.variant());
+        self.set.remove_exact(value)
+    }
+
+    /// Removes and returns the value in the set, if any, that is equal to the given value. See `VariantSet::take`.
+    pub fn take(&mut self, value: T::Variant) -> Option<T> {
+        self.remove(value)
+    }
+
+    /// Clears the set, removing all values. Every variant that was present is recorded as removed.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let mut set = TrackedVariantSet::new();
+    /// set.set(MyEnum::Variant2(42));
+    /// set.take_changes();
+    ///
+    /// set.clear();
+    /// assert_eq!(set.take_changes().removed, vec![MyEnumVariant::Variant2]);
+    /// ```
+    pub fn clear(&mut self) {
+        self.touch_all();
+        self.set.clear();
+    }
+
+    /// Clears the set, returning all elements as an iterator. Every variant that was present is recorded as removed.
+    /// See `VariantSet::drain`.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.touch_all();
+        self.set.drain()
+    }
+
+    fn touch_all(&mut self) {
+        for variant in self.set.variants() {
+            self.touched.entry(variant).or_insert(true);
+        }
+    }
+}
+
+impl<T> Default for TrackedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for TrackedVariantSet<T>
+where
+    T: VariantEnum + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            set: self.set.clone(),
+            touched: self.touched.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for TrackedVariantSet<T>
+where
+    T: VariantEnum + std::fmt::Debug,
+    T::Variant: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrackedVariantSet")
+            .field("set", &self.set)
+            .field("touched", &self.touched)
+            .finish()
+    }
+}
+
+impl<T> Deref for TrackedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    type Target = VariantSet<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.set
+    }
+}
+
+impl<T> From<VariantSet<T>> for TrackedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    /// Starts tracking changes to an existing set. The current contents are the baseline for the first `take_changes`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{TrackedVariantSet, VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum MyEnum {
+    ///     Variant1(String),
+    ///     Variant2(u32),
+    /// }
+    ///
+    /// let set = VariantSet::from([MyEnum::Variant2(42)]);
+    /// let mut tracked = TrackedVariantSet::from(set);
+    /// assert!(tracked.contains(MyEnumVariant::Variant2));
+    /// assert!(tracked.take_changes().is_empty());
+    /// ```
+    fn from(set: VariantSet<T>) -> Self {
+        Self {
+            set,
+            touched: HashMap::with_hasher(VariantHasher::<T>::default()),
+        }
+    }
+}
+
+impl<T> Extend<T> for TrackedVariantSet<T>
+where
+    T: VariantEnum,
+{
+    /// Extends the set with the contents of an iterator, recording each value as if it was passed to `set`.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.set(value);
+        }
+    }
+}
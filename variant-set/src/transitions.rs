@@ -0,0 +1,348 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Write},
+};
+
+use crate::{FiniteKey, InvalidTransition, VariantEnum, VariantKey};
+
+/// A trait for variant enums with transitions declared on their variants with `#[variant(transitions_to(...))]`.
+///
+/// `#[derive(VariantEnum)]` implements this trait for the variant enum when at least one variant declares its
+/// transitions. Variants with key fields cannot declare transitions.
+///
+/// # Examples
+/// ```
+/// use variant_set::{DeclaredTransitions, VariantEnum};
+///
+/// #[derive(VariantEnum)]
+/// enum Job {
+///     #[variant(transitions_to(Running))]
+///     Queued,
+///     #[variant(transitions_to(Done, Failed))]
+///     Running { progress: f32 },
+///     Done,
+///     #[variant(transitions_to(Queued))]
+///     Failed(String),
+/// }
+///
+/// let transitions = JobVariant::transitions();
+/// assert!(transitions.is_allowed(JobVariant::Running, JobVariant::Failed));
+/// assert!(!transitions.is_allowed(JobVariant::Done, JobVariant::Running));
+/// ```
+pub trait DeclaredTransitions: VariantKey {
+    /// Returns the transitions declared on the variants.
+    fn transitions() -> VariantTransitions<Self>;
+}
+
+/// A table of the allowed transitions between the variants of an enum, for enums modelling the states of a state
+/// machine.
+///
+/// Transitions are declared as edges from a variant to another, either with `allow`, or with
+/// `#[variant(transitions_to(...))]` on the variants of a `#[derive(VariantEnum)]` enum, in which case the table is
+/// returned by `DeclaredTransitions::transitions`.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantTransitions};
+///
+/// #[derive(VariantEnum)]
+/// enum Door {
+///     Open,
+///     Closed,
+///     Locked(u32),
+/// }
+///
+/// let mut transitions = VariantTransitions::new();
+/// transitions
+///     .allow(DoorVariant::Open, DoorVariant::Closed)
+///     .allow(DoorVariant::Closed, DoorVariant::Open)
+///     .allow(DoorVariant::Closed, DoorVariant::Locked)
+///     .allow(DoorVariant::Locked, DoorVariant::Closed);
+///
+/// assert!(transitions.check(&Door::Closed, &Door::Locked(1234)).is_ok());
+/// assert!(transitions.check(&Door::Open, &Door::Locked(1234)).is_err());
+/// assert!(transitions.can_reach(DoorVariant::Open, DoorVariant::Locked));
+/// ```
+pub struct VariantTransitions<V>
+where
+    V: VariantKey,
+{
+    edges: HashMap<V, Vec<V>, V::BuildHasher>,
+}
+
+impl<V> VariantTransitions<V>
+where
+    V: VariantKey,
+{
+    /// Creates a table without any allowed transition.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantTransitions};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Door {
+    ///     Open,
+    ///     Closed,
+    /// }
+    ///
+    /// let transitions = VariantTransitions::new();
+    /// assert!(!transitions.is_allowed(DoorVariant::Open, DoorVariant::Closed));
+    /// ```
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            edges: HashMap::with_hasher(V::BuildHasher::default()),
+        }
+    }
+
+    /// Allows the transition from a variant to another. Returns the table, so that transitions can be chained.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantTransitions};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Door {
+    ///     Open,
+    ///     Closed,
+    /// }
+    ///
+    /// let mut transitions = VariantTransitions::new();
+    /// transitions.allow(DoorVariant::Open, DoorVariant::Closed);
+    ///
+    /// assert!(transitions.is_allowed(DoorVariant::Open, DoorVariant::Closed));
+    /// assert!(!transitions.is_allowed(DoorVariant::Closed, DoorVariant::Open));
+    /// ```
+    pub fn allow(&mut self, from: V, to: V) -> &mut Self {
+        let targets = self.edges.entry(from).or_default();
+        if !targets.contains(&to) {
+            targets.push(to);
+        }
+        self
+    }
+
+    /// Returns `true` if the transition from a variant to another was allowed.
+    ///
+    /// A variant can only transition to itself if it was explicitly allowed to, unlike in `check`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantTransitions};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Door {
+    ///     Open,
+    ///     Closed,
+    /// }
+    ///
+    /// let mut transitions = VariantTransitions::new();
+    /// transitions.allow(DoorVariant::Open, DoorVariant::Closed);
+    ///
+    /// assert!(transitions.is_allowed(DoorVariant::Open, DoorVariant::Closed));
+    /// assert!(!transitions.is_allowed(DoorVariant::Open, DoorVariant::Open));
+    /// ```
+    #[must_use]
+    pub fn is_allowed(&self, from: V, to: V) -> bool {
+        self.targets(from).contains(&to)
+    }
+
+    /// Returns the variants a variant can transition to directly, in the order they were allowed.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{DeclaredTransitions, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Job {
+    ///     #[variant(transitions_to(Done, Failed))]
+    ///     Running,
+    ///     Done,
+    ///     Failed,
+    /// }
+    ///
+    /// let transitions = JobVariant::transitions();
+    /// assert_eq!(transitions.targets(JobVariant::Running), [JobVariant::Done, JobVariant::Failed]);
+    /// assert!(transitions.targets(JobVariant::Done).is_empty());
+    /// ```
+    #[must_use]
+    pub fn targets(&self, from: V) -> &[V] {
+        self.edges.get(&from).map_or(&[], Vec::as_slice)
+    }
+
+    /// Checks that a value can be replaced by another, which is the case if they are of the same variant, or if the
+    /// transition between their variants is allowed.
+    ///
+    /// # Errors
+    /// Returns an `InvalidTransition` with both variants if the transition is not allowed.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{DeclaredTransitions, InvalidTransition, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Job {
+    ///     #[variant(transitions_to(Running))]
+    ///     Queued,
+    ///     #[variant(transitions_to(Done))]
+    ///     Running { progress: f32 },
+    ///     Done,
+    /// }
+    ///
+    /// let transitions = JobVariant::transitions();
+    ///
+    /// assert!(transitions.check(&Job::Queued, &Job::Running { progress: 0.0 }).is_ok());
+    /// assert!(transitions.check(&Job::Running { progress: 0.0 }, &Job::Running { progress: 0.5 }).is_ok());
+    /// assert_eq!(
+    ///     transitions.check(&Job::Done, &Job::Queued),
+    ///     Err(InvalidTransition { from: JobVariant::Done, to: JobVariant::Queued }),
+    /// );
+    /// ```
+    pub fn check<T>(&self, old: &T, new: &T) -> Result<(), InvalidTransition<V>>
+    where
+        T: VariantEnum<Variant = V>,
+    {
+        let (from, to) = (old.variant(), new.variant());
+        if from == to || self.is_allowed(from, to) {
+            Ok(())
+        } else {
+            Err(InvalidTransition { from, to })
+        }
+    }
+
+    /// Returns the variants that can be reached from a variant in one or more transitions, nearest first. The variant
+    /// itself is only included if it is part of a cycle.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{DeclaredTransitions, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Job {
+    ///     #[variant(transitions_to(Running))]
+    ///     Queued,
+    ///     #[variant(transitions_to(Done, Failed))]
+    ///     Running,
+    ///     Done,
+    ///     #[variant(transitions_to(Queued))]
+    ///     Failed,
+    /// }
+    ///
+    /// let transitions = JobVariant::transitions();
+    /// assert_eq!(
+    ///     transitions.reachable_from(JobVariant::Running),
+    ///     [JobVariant::Done, JobVariant::Failed, JobVariant::Queued, JobVariant::Running],
+    /// );
+    /// assert!(transitions.reachable_from(JobVariant::Done).is_empty());
+    /// ```
+    #[must_use]
+    pub fn reachable_from(&self, from: V) -> Vec<V> {
+        let mut reached = Vec::new();
+        let mut seen = HashSet::with_hasher(V::BuildHasher::default());
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(variant) = queue.pop_front() {
+            for &target in self.targets(variant) {
+                if seen.insert(target) {
+                    reached.push(target);
+                    queue.push_back(target);
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Returns `true` if a variant can be reached from another in zero or more transitions.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{DeclaredTransitions, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Job {
+    ///     #[variant(transitions_to(Running))]
+    ///     Queued,
+    ///     #[variant(transitions_to(Done))]
+    ///     Running,
+    ///     Done,
+    /// }
+    ///
+    /// let transitions = JobVariant::transitions();
+    /// assert!(transitions.can_reach(JobVariant::Queued, JobVariant::Done));
+    /// assert!(transitions.can_reach(JobVariant::Done, JobVariant::Done));
+    /// assert!(!transitions.can_reach(JobVariant::Done, JobVariant::Queued));
+    /// ```
+    #[must_use]
+    pub fn can_reach(&self, from: V, to: V) -> bool {
+        from == to || self.reachable_from(from).contains(&to)
+    }
+
+    /// Returns the transitions as a graph in the DOT language of Graphviz, with a node per variant and an edge per
+    /// allowed transition, in declaration order.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{DeclaredTransitions, VariantEnum};
+    ///
+    /// #[derive(VariantEnum)]
+    /// enum Job {
+    ///     #[variant(transitions_to(Running))]
+    ///     Queued,
+    ///     #[variant(transitions_to(Done))]
+    ///     Running,
+    ///     Done,
+    /// }
+    ///
+    /// assert_eq!(
+    ///     JobVariant::transitions().to_dot(),
+    ///     "digraph {\n    Queued;\n    Running;\n    Done;\n    Queued -> Running;\n    Running -> Done;\n}\n",
+    /// );
+    /// ```
+    #[must_use]
+    pub fn to_dot(&self) -> String
+    where
+        V: FiniteKey,
+    {
+        let mut dot = String::from("digraph {\n");
+        for variant in V::ALL {
+            let _ = writeln!(dot, "    {};", variant.name());
+        }
+        for &from in V::ALL {
+            for to in self.targets(from) {
+                let _ = writeln!(dot, "    {} -> {};", from.name(), to.name());
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<V> Default for VariantTransitions<V>
+where
+    V: VariantKey,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Clone for VariantTransitions<V>
+where
+    V: VariantKey,
+{
+    fn clone(&self) -> Self {
+        Self {
+            edges: self.edges.clone(),
+        }
+    }
+}
+
+impl<V> fmt::Debug for VariantTransitions<V>
+where
+    V: VariantKey + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(&self.edges).finish()
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{VariantEnum, VariantSet, VariantStorage};
+
+/// A trait for enums whose values can be passed to a visitor of type `V`, by calling the method of the visitor for
+/// their variant.
+///
+/// `#[variant_enum(visitor)]` on a `#[derive(VariantEnum)]` enum generates a `{Name}Visitor` trait with one method
+/// per variant, and implements this trait for every type implementing it. The methods have no default, so adding a
+/// variant to the enum fails to compile until every visitor handles it.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantVisit};
+///
+/// #[derive(VariantEnum)]
+/// #[variant_enum(visitor)]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Square(f64),
+///     Empty,
+/// }
+///
+/// struct Area(f64);
+///
+/// impl ShapeVisitor for Area {
+///     fn visit_circle(&mut self, radius: &f64) {
+///         self.0 += std::f64::consts::PI * radius * radius;
+///     }
+///
+///     fn visit_square(&mut self, square: &f64) {
+///         self.0 += square * square;
+///     }
+///
+///     fn visit_empty(&mut self) {}
+/// }
+///
+/// let mut area = Area(0.0);
+/// Shape::Square(2.0).accept(&mut area);
+/// Shape::Empty.accept(&mut area);
+/// assert_eq!(area.0, 4.0);
+/// ```
+pub trait VariantVisit<V: ?Sized>: VariantEnum {
+    /// Calls the method of the visitor for the variant of the value, with references to its fields.
+    fn accept(&self, visitor: &mut V);
+}
+
+/// A trait for enums whose values can be passed to a visitor of type `V` that modifies them, by calling the method of
+/// the visitor for their variant.
+///
+/// `#[variant_enum(visitor)]` on a `#[derive(VariantEnum)]` enum generates a `{Name}VisitorMut` trait with one method
+/// per variant, receiving mutable references to the fields, and implements this trait for every type implementing it.
+/// Fields marked with `#[variant(key)]` are part of the variant, so they are only received by shared reference.
+///
+/// # Examples
+/// ```
+/// use variant_set::{VariantEnum, VariantVisitMut};
+///
+/// #[derive(VariantEnum, Debug, PartialEq)]
+/// #[variant_enum(visitor)]
+/// enum Shape {
+///     Circle { radius: f64 },
+///     Square(f64),
+///     Empty,
+/// }
+///
+/// struct Scale(f64);
+///
+/// impl ShapeVisitorMut for Scale {
+///     fn visit_circle(&mut self, radius: &mut f64) {
+///         *radius *= self.0;
+///     }
+///
+///     fn visit_square(&mut self, square: &mut f64) {
+///         *square *= self.0;
+///     }
+///
+///     fn visit_empty(&mut self) {}
+/// }
+///
+/// let mut shape = Shape::Circle { radius: 1.5 };
+/// shape.accept_mut(&mut Scale(2.0));
+/// assert_eq!(shape, Shape::Circle { radius: 3.0 });
+/// ```
+pub trait VariantVisitMut<V: ?Sized>: VariantEnum {
+    /// Calls the method of the visitor for the variant of the value, with mutable references to its fields.
+    fn accept_mut(&mut self, visitor: &mut V);
+}
+
+impl<T, S> VariantSet<T, S>
+where
+    T: VariantEnum,
+    S: VariantStorage<T>,
+{
+    /// Passes every value of the set to the visitor, in the same order as `iter`.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum)]
+    /// #[variant_enum(visitor)]
+    /// enum Setting {
+    ///     Volume(u8),
+    ///     Theme { name: String },
+    ///     Muted,
+    /// }
+    ///
+    /// struct Describe(Vec<String>);
+    ///
+    /// impl SettingVisitor for Describe {
+    ///     fn visit_volume(&mut self, volume: &u8) {
+    ///         self.0.push(format!("volume {volume}"));
+    ///     }
+    ///
+    ///     fn visit_theme(&mut self, name: &String) {
+    ///         self.0.push(format!("theme {name}"));
+    ///     }
+    ///
+    ///     fn visit_muted(&mut self) {
+    ///         self.0.push("muted".to_string());
+    ///     }
+    /// }
+    ///
+    /// let set = VariantSet::from([Setting::Volume(7), Setting::Muted]);
+    ///
+    /// let mut describe = Describe(Vec::new());
+    /// set.visit(&mut describe);
+    /// describe.0.sort();
+    /// assert_eq!(describe.0, ["muted", "volume 7"]);
+    /// ```
+    pub fn visit<V>(&self, visitor: &mut V)
+    where
+        V: ?Sized,
+        T: VariantVisit<V>,
+    {
+        for value in self {
+            value.accept(visitor);
+        }
+    }
+
+    /// Passes a mutable reference to every value of the set to the visitor, in the same order as `iter`.
+    ///
+    /// Unlike with `iter_mut`, the visitor only receives the fields of each value, and key fields only by shared
+    /// reference, so it cannot change their variant.
+    ///
+    /// # Examples
+    /// ```
+    /// use variant_set::{VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// #[variant_enum(visitor)]
+    /// enum Setting {
+    ///     Volume(u8),
+    ///     Theme { name: String },
+    ///     Muted,
+    /// }
+    ///
+    /// struct Reset;
+    ///
+    /// impl SettingVisitorMut for Reset {
+    ///     fn visit_volume(&mut self, volume: &mut u8) {
+    ///         *volume = 5;
+    ///     }
+    ///
+    ///     fn visit_theme(&mut self, name: &mut String) {
+    ///         *name = "light".to_string();
+    ///     }
+    ///
+    ///     fn visit_muted(&mut self) {}
+    /// }
+    ///
+    /// let mut set = VariantSet::from([Setting::Volume(9), Setting::Theme { name: "dark".to_string() }]);
+    /// set.visit_mut(&mut Reset);
+    ///
+    /// assert_eq!(set.get(SettingVariant::Volume), Some(&Setting::Volume(5)));
+    /// assert_eq!(set.get(SettingVariant::Theme), Some(&Setting::Theme { name: "light".to_string() }));
+    /// ```
+    ///
+    /// Key fields are passed by shared reference, so that values stay stored under their key:
+    /// ```
+    /// use variant_set::{VariantEnum, VariantSet};
+    ///
+    /// #[derive(VariantEnum, Debug, PartialEq)]
+    /// #[variant_enum(visitor)]
+    /// enum Header {
+    ///     Custom {
+    ///         #[variant(key)]
+    ///         id: u16,
+    ///         value: String,
+    ///     },
+    ///     Empty,
+    /// }
+    ///
+    /// struct Redact;
+    ///
+    /// impl HeaderVisitorMut for Redact {
+    ///     fn visit_custom(&mut self, id: &u16, value: &mut String) {
+    ///         if *id == 7 {
+    ///             *value = "***".to_string();
+    ///         }
+    ///     }
+    ///
+    ///     fn visit_empty(&mut self) {}
+    /// }
+    ///
+    /// let mut set = VariantSet::from([
+    ///     Header::Custom { id: 7, value: "secret".to_string() },
+    ///     Header::Custom { id: 8, value: "public".to_string() },
+    /// ]);
+    /// set.visit_mut(&mut Redact);
+    ///
+    /// assert_eq!(set.get(HeaderVariant::Custom(7)), Some(&Header::Custom { id: 7, value: "***".to_string() }));
+    /// assert_eq!(set.get(HeaderVariant::Custom(8)), Some(&Header::Custom { id: 8, value: "public".to_string() }));
+    /// ```
+    pub fn visit_mut<V>(&mut self, visitor: &mut V)
+    where
+        V: ?Sized,
+        T: VariantVisitMut<V>,
+    {
+        for value in self.iter_mut() {
+            value.accept_mut(visitor);
+        }
+    }
+}